@@ -58,9 +58,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<64>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M64>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -69,9 +66,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<128>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M128>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -81,9 +75,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<265>", &lines, |b, lines| {
         let mut counter: HyperTwoBits<_> = HyperTwoBits::<h2b::M256>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -93,9 +84,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<512>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M512>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -104,9 +92,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<1024>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M1024>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -115,9 +100,6 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<2048>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M2048>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
@@ -126,14 +108,46 @@ fn bench_ulysses(c: &mut Criterion) {
     group.bench_with_input("HyperTwoBits<4096>", &lines, |b, lines| {
         let mut counter = HyperTwoBits::<h2b::M4096>::default();
         b.iter(|| {
-            // for line in lines.chunks_exact(4) {
-            //     counter.insert4(&line[0], &line[1], &line[2], &line[3]);
-            // }
             for line in lines {
                 counter.insert(line);
             }
         });
     });
+    group.bench_with_input("HyperTwoBits<4096> insert4", &lines, |b, lines| {
+        let mut counter = HyperTwoBits::<h2b::M4096>::default();
+        b.iter(|| {
+            let mut chunks = lines.chunks_exact(4);
+            for chunk in &mut chunks {
+                counter.insert4(&chunk[0], &chunk[1], &chunk[2], &chunk[3]);
+            }
+            for line in chunks.remainder() {
+                counter.insert(line);
+            }
+        });
+    });
+    group.bench_with_input(
+        "HyperTwoBits<4096> insert_array::<8>",
+        &lines,
+        |b, lines| {
+            let mut counter = HyperTwoBits::<h2b::M4096>::default();
+            b.iter(|| {
+                let mut chunks = lines.chunks_exact(8);
+                for chunk in &mut chunks {
+                    let array: &[String; 8] = chunk.try_into().unwrap();
+                    counter.insert_array(array);
+                }
+                for line in chunks.remainder() {
+                    counter.insert(line);
+                }
+            });
+        },
+    );
+    group.bench_with_input("HyperTwoBits<4096> insert_chunk", &lines, |b, lines| {
+        let mut counter = HyperTwoBits::<h2b::M4096>::default();
+        b.iter(|| {
+            counter.insert_chunk(lines);
+        });
+    });
     group.bench_with_input("HyperThreeBits<64>", &lines, |b, lines| {
         let mut counter = HyperThreeBits::<h3b::M64>::default();
         b.iter(|| {
@@ -194,5 +208,45 @@ fn bench_ulysses(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_ulysses);
+fn bench_count(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Count");
+
+    // With the `fast-math` feature enabled, `count()` uses a bit-trick-plus-polynomial
+    // `ln` approximation instead of `f64::ln`; run this benchmark with and without
+    // `--features fast-math` to compare throughput.
+    group.bench_function("HyperTwoBits<4096>", |b| {
+        let mut counter = HyperTwoBits::<h2b::M4096>::default();
+        for i in 0..10_000u64 {
+            counter.insert(&i);
+        }
+        b.iter(|| counter.count());
+    });
+
+    group.finish();
+}
+
+fn bench_merge_fan_in(c: &mut Criterion) {
+    let mut group = c.benchmark_group("MergeFanIn64");
+
+    group.bench_function("HyperTwoBits<4096>", |b| {
+        b.iter(|| {
+            let mut shards: Vec<HyperTwoBits<h2b::M4096>> = (0..64u64)
+                .map(|shard| {
+                    let mut htb = HyperTwoBits::<h2b::M4096>::default();
+                    for i in 0..1_000u64 {
+                        htb.insert(&(shard * 1_000 + i));
+                    }
+                    htb
+                })
+                .collect();
+            let mut merged = shards.remove(0);
+            merged.merge_from_slice(&shards);
+            merged
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_ulysses, bench_count, bench_merge_fan_in);
 criterion_main!(benches);
@@ -0,0 +1,109 @@
+//! Compares `HyperTwoBits`, `HyperThreeBits`, and `HyperLogLogPlus` at (approximately)
+//! equal memory, per the filed methodology issue that the crate's other benchmarks pick
+//! `M` per algorithm without accounting for `h2b`'s 2-bit and `h3b`'s 3-bit substreams
+//! costing a different number of bits for the same `M`. `M` is only available as sizes
+//! this crate ships pre-built sketches for (powers of two, see `h2b::M64`..`M8192` /
+//! `h3b::M64`..`M4096`) and `HyperLogLogPlus`'s register count is `2^precision`, so an
+//! exact bit match isn't always possible -- each algorithm here is sized to the largest
+//! configuration that does not exceed `BUDGET_BITS`, and the actual bit counts are
+//! printed so the comparison's honesty can be checked at a glance.
+use std::hash::RandomState;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use hyperloglogplus::{HyperLogLog as _, HyperLogLogPlus};
+use hypertwobits::h2b::{self, HyperTwoBits};
+use hypertwobits::h3b::{self, HyperThreeBits};
+
+/// Memory budget the three sketches below are sized against, anchored to
+/// `h2b::M4096`'s exact bit cost (`4096 * 2`) so at least one algorithm hits the budget
+/// exactly.
+const BUDGET_BITS: usize = h2b::theoretical_bits(4096);
+/// `HyperLogLogPlus`'s registers are 6 bits each (a leading-zero-run count plus
+/// stochastic-averaging correction bookkeeping); `2^HLLP_PRECISION` of them must fit
+/// within `BUDGET_BITS`.
+const HLLP_PRECISION: usize = 10;
+
+fn load_ulysses() -> Vec<String> {
+    use std::io::BufRead as _;
+    let file = std::fs::File::open("data/Ulysses.csv").unwrap();
+    std::io::BufReader::new(file)
+        .lines()
+        .collect::<Result<Vec<String>, _>>()
+        .unwrap()
+}
+
+/// Prints each algorithm's actual bit cost at this benchmark's chosen `M`/precision,
+/// and its relative error against an exact `HashSet` count over the full corpus, so
+/// throughput numbers below can be read alongside the accuracy they bought.
+fn print_normalized_accuracy(lines: &[String]) {
+    println!("target budget: {BUDGET_BITS} bits");
+    let exact = lines.iter().collect::<std::collections::HashSet<_>>().len() as f64;
+
+    let mut h2b = HyperTwoBits::<h2b::M4096>::default();
+    let mut h3b = HyperThreeBits::<h3b::M2048>::default();
+    let mut hllp: HyperLogLogPlus<String, RandomState> =
+        HyperLogLogPlus::new(HLLP_PRECISION as u8, RandomState::new()).unwrap();
+    for line in lines {
+        h2b.insert(line);
+        h3b.insert(line);
+        hllp.insert(line);
+    }
+
+    let relative_error = |estimate: f64| (estimate - exact).abs() / exact;
+    println!(
+        "HyperTwoBits<M4096>:    {} bits, relative error {:.4}",
+        h2b::theoretical_bits(4096),
+        relative_error(h2b.count_f64())
+    );
+    println!(
+        "HyperThreeBits<M2048>:  {} bits, relative error {:.4}",
+        h3b::theoretical_bits(2048),
+        relative_error(h3b.count() as f64)
+    );
+    println!(
+        "HyperLogLogPlus<p={HLLP_PRECISION}>: {} bits, relative error {:.4}",
+        6 * (1usize << HLLP_PRECISION),
+        relative_error(hllp.count())
+    );
+}
+
+fn bench_normalized(c: &mut Criterion) {
+    let lines = load_ulysses();
+    print_normalized_accuracy(&lines);
+
+    let mut group = c.benchmark_group("NormalizedAtEqualBits");
+    group.throughput(Throughput::Elements(lines.len() as u64));
+
+    group.bench_with_input("HyperTwoBits<M4096>", &lines, |b, lines| {
+        let mut counter = HyperTwoBits::<h2b::M4096>::default();
+        b.iter(|| {
+            for line in lines {
+                counter.insert(line);
+            }
+        });
+    });
+
+    group.bench_with_input("HyperThreeBits<M2048>", &lines, |b, lines| {
+        let mut counter = HyperThreeBits::<h3b::M2048>::default();
+        b.iter(|| {
+            for line in lines {
+                counter.insert(line);
+            }
+        });
+    });
+
+    group.bench_with_input("HyperLogLogPlus", &lines, |b, lines| {
+        let mut counter: HyperLogLogPlus<String, RandomState> =
+            HyperLogLogPlus::new(HLLP_PRECISION as u8, RandomState::new()).unwrap();
+        b.iter(|| {
+            for line in lines {
+                counter.insert(line);
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_normalized);
+criterion_main!(benches);
@@ -114,13 +114,17 @@ fn h2b<BITS: h2b::Sketch, HASH: BuildHasher + Default>(
     let mut r = Resultset::new(algo, n);
     for _ in 0..n {
         let mut counter: h2b::HyperTwoBits<BITS, HASH> = h2b::HyperTwoBits::new();
+        let mut prev = 0;
         for (i, w) in data.iter().enumerate() {
             counter.insert(w);
+            let count = counter.count();
+            counter.assert_monotonic(prev);
+            prev = count;
             match i {
-                100 => r.results_100.push(counter.count()),
-                1_000 => r.results_1_000.push(counter.count()),
-                10_000 => r.results_10_000.push(counter.count()),
-                100_000 => r.results_100_000.push(counter.count()),
+                100 => r.results_100.push(count),
+                1_000 => r.results_1_000.push(count),
+                10_000 => r.results_10_000.push(count),
+                100_000 => r.results_100_000.push(count),
                 _ => {}
             }
             r.total += 1;
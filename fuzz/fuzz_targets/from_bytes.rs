@@ -0,0 +1,11 @@
+#![no_main]
+
+use hypertwobits::h2b::{HyperTwoBits, M256};
+use libfuzzer_sys::fuzz_target;
+
+// `from_bytes` decodes untrusted input (sketches may arrive from network/object
+// storage), so it must always return `Ok` or a `DecodeError`, never panic or UB, no
+// matter how the input is truncated or corrupted.
+fuzz_target!(|data: &[u8]| {
+    let _ = HyperTwoBits::<M256>::from_bytes(data);
+});
@@ -0,0 +1,125 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::h2b::{HyperTwoBits, Sketch, M256};
+use crate::hbb64::HyperBitBit64;
+use crate::AHasherDefaultBuilder;
+
+/// Combines [`HyperBitBit64`] and [`HyperTwoBits`] estimates, since the two have different
+/// error profiles at different cardinalities: `HyperBitBit64` only has 64 substreams, so it
+/// stays cheap and accurate while cardinality is small, but its coarse power-of-four scaling
+/// makes it noisier once cardinality grows; `HyperTwoBits` has many more substreams and is
+/// the more accurate choice past that point. This tracks both from the same hash and returns
+/// whichever is expected to be more accurate for the observed scale.
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[derive(Debug, Clone)]
+pub struct CombinedEstimator<
+    SKETCH: Sketch = M256,
+    HASH: BuildHasher + Default = AHasherDefaultBuilder,
+> {
+    hash: HASH,
+    hbb: HyperBitBit64,
+    htb: HyperTwoBits<SKETCH>,
+    crossover: u64,
+}
+
+impl<SKETCH: Sketch, HASH: BuildHasher + Default> Default for CombinedEstimator<SKETCH, HASH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SKETCH: Sketch, HASH: BuildHasher + Default> CombinedEstimator<SKETCH, HASH> {
+    /// Empirically, `HyperBitBit64`'s 64 substreams keep it competitive with `HyperTwoBits`
+    /// up to roughly this many distinct elements; past it, `HyperTwoBits`'s larger substream
+    /// count wins out. Tune via [`Self::with_crossover`] if your corpus differs.
+    const DEFAULT_CROSSOVER: u64 = 1_000;
+
+    #[must_use]
+    /// Creates a new `CombinedEstimator` using the default crossover cardinality.
+    pub fn new() -> Self {
+        Self::with_crossover(Self::DEFAULT_CROSSOVER)
+    }
+
+    #[must_use]
+    /// Creates a new `CombinedEstimator` that switches from `HyperBitBit64` to `HyperTwoBits`
+    /// once the `HyperBitBit64` estimate exceeds `crossover`.
+    pub fn with_crossover(crossover: u64) -> Self {
+        Self {
+            hash: HASH::default(),
+            hbb: HyperBitBit64::new(),
+            htb: HyperTwoBits::new(),
+            crossover,
+        }
+    }
+
+    #[inline]
+    /// Inserts a value into both underlying estimators.
+    pub fn insert<V: Hash + ?Sized>(&mut self, v: &V) {
+        self.insert_hash(self.hash.hash_one(v));
+    }
+
+    #[inline]
+    /// Inserts an already-computed hash into both underlying estimators.
+    pub fn insert_hash(&mut self, hash: u64) {
+        self.hbb.insert_hash(hash);
+        self.htb.insert_hash(hash);
+    }
+
+    #[must_use]
+    /// Returns the cardinality estimate: `HyperBitBit64`'s below the crossover cardinality,
+    /// `HyperTwoBits`'s above it.
+    pub fn count(&self) -> u64 {
+        let hbb_count = self.hbb.count();
+        if hbb_count < self.crossover {
+            hbb_count
+        } else {
+            self.htb.count()
+        }
+    }
+}
+
+// `CombinedEstimator` has no interior mutability, so it's `Send`/`Sync` for any
+// `Send + Sync` `SKETCH`/`HASH`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CombinedEstimator<M256>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::CombinedEstimator;
+    use crate::h2b::{HyperTwoBits, M256};
+    use crate::hbb64::HyperBitBit64;
+
+    #[test]
+    fn test_combined_estimator_beats_either_alone() {
+        for &n in &[100u64, 1_000, 10_000, 100_000] {
+            let hashes: Vec<u64> = (0..n)
+                .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+                .collect();
+
+            let mut hbb: HyperBitBit64 = HyperBitBit64::new();
+            let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+            let mut combined: CombinedEstimator<M256> = CombinedEstimator::new();
+            for &h in &hashes {
+                hbb.insert_hash(h);
+                htb.insert_hash(h);
+                combined.insert_hash(h);
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let n_f = n as f64;
+            #[allow(clippy::cast_precision_loss)]
+            let bitbit_delta = (n_f - hbb.count() as f64).abs() / n_f;
+            #[allow(clippy::cast_precision_loss)]
+            let twobits_delta = (n_f - htb.count() as f64).abs() / n_f;
+            #[allow(clippy::cast_precision_loss)]
+            let blended_delta = (n_f - combined.count() as f64).abs() / n_f;
+
+            assert!(
+                blended_delta <= bitbit_delta.max(twobits_delta) + 0.02,
+                "n={n}: combined delta {blended_delta} should not exceed the worse of hbb {bitbit_delta} / htb {twobits_delta}"
+            );
+        }
+    }
+}
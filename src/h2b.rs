@@ -2,38 +2,304 @@ pub(super) mod sketch;
 #[cfg(test)]
 mod tests;
 
-use std::hash::BuildHasher;
+use std::hash::{BuildHasher, Hash, Hasher};
 
-pub use sketch::{Sketch, M1024, M128, M2048, M256, M4096, M512, M64};
+pub use sketch::{Sketch, M1024, M128, M2048, M256, M4096, M512, M64, M8192};
 
-use crate::AHasherDefaultBuilder;
+use crate::{AHasherDefaultBuilder, Seedable};
+
+/// Number of hash values kept in [`HyperTwoBits::minhash`]'s bottom-k sample, under the
+/// `minhash` feature. Larger values make [`HyperTwoBits::minhash_jaccard`] more
+/// accurate at the cost of `8 * MINHASH_K` extra bytes per sketch.
+#[cfg(feature = "minhash")]
+const MINHASH_K: usize = 64;
+
+/// Number of `count()` snapshots kept in [`HyperTwoBits::estimate_quantile`]'s ring
+/// buffer, under the `history` feature. Once full, each new snapshot overwrites the
+/// oldest, so this bounds how far back a quantile query can see, not how long the
+/// counter has been running.
+#[cfg(feature = "history")]
+const HISTORY_CAPACITY: usize = 32;
 
 /// `HyperTwoBits` implementation that is fully stack allocated and generic to avoid branches for
 /// different numbers of sub streams.
 ///
-/// Both the hasher and the sub stream size siaz can be customized, by default it uses `AHasherBuilder` and `M256`
+/// Both the hasher and the sub stream size siaz can be customized, by default it uses `AHasherBuilder` and `M256`.
+///
+/// `RESCALE_STEP` is the amount `t` advances on each rescale, and the spacing between
+/// value-ladder tiers (`t`, `t + RESCALE_STEP`, `t + 2 * RESCALE_STEP`). The default of
+/// `4` matches the paper's base-16 scaling (`2^4 = 16` between tiers); changing it
+/// trades resolution for a different growth rate between rescales.
+///
+/// `DECREMENT_STEPS` is how many single-step decrements a rescale applies to the
+/// sketch (each one halves the surviving substreams' implied resolution), with `t`
+/// advancing by `RESCALE_STEP * DECREMENT_STEPS` to match. The default of `1`
+/// reproduces the paper's behavior; raising it rescales less often but coarsens
+/// resolution faster each time it does, trading accuracy for a smaller `t`/count
+/// bookkeeping overhead on very high-cardinality streams.
+///
+/// `MERGE_FULL_PER_MILLE` is the fill fraction of `BITS::STREAMS` (out of `1000`) at
+/// which [`Self::merge_detailed`]/[`Self::merge_rescaled`] pre-emptively rescale `self`
+/// before folding `other` in, see [`Self::merge_full_fraction`]. The default of `990`
+/// (0.99) is distinct from [`Self::ALPHA`]'s 0.988 used for the analogous per-insert
+/// threshold: merge only pays this check once per call rather than once per insert, so
+/// there's less value in rescaling early the way the per-insert threshold does -- this
+/// stays looser and only kicks in once the sketch is truly nearly full. Unlike `ALPHA`,
+/// which is baked in because it's read from the hot per-insert path, this is a type
+/// parameter (rather than a runtime field) so tuning it costs nothing at runtime and
+/// doesn't change `size_of::<Self>()`.
 #[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-pub struct HyperTwoBits<SKETCH: Sketch = M256, HASH: BuildHasher = AHasherDefaultBuilder> {
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    not(any(feature = "minhash", feature = "history")),
+    derive(Eq, PartialEq, Hash)
+)]
+pub struct HyperTwoBits<
+    SKETCH: Sketch = M256,
+    HASH: BuildHasher = AHasherDefaultBuilder,
+    const RESCALE_STEP: u32 = 4,
+    const DECREMENT_STEPS: u32 = 1,
+    const MERGE_FULL_PER_MILLE: u32 = 990,
+> {
     hash: HASH,
     sketch: SKETCH,
+    /// Cached number of active substreams, kept in sync with [`Sketch::count`] on every
+    /// insert/rescale so [`Self::count`] and the hot `insert_split` threshold check
+    /// don't have to recompute it from `sketch` (a popcount) on every call. Dropped
+    /// under the `compact` feature, trading that recomputation for one fewer field --
+    /// [`Self::active_count`] hides the difference from the rest of the crate; see it
+    /// for the accessor both builds share. Whether dropping the field actually shrinks
+    /// `size_of::<Self>()` depends on how much padding the rest of the fields leave
+    /// behind for a given `SKETCH`/`HASH`/feature combination; the struct-size
+    /// assertions below this module track the real numbers rather than assuming one.
+    #[cfg(not(feature = "compact"))]
     count: u32,
     t: u32,
+    /// Number of real merges ([`Self::merge`]/[`Self::merge_detailed`]) folded into this
+    /// sketch so far, see [`Self::count_merge_corrected`]. A [`MergeOutcome::Discarded`]
+    /// merge doesn't touch `self`'s data, so it doesn't advance this.
+    merge_depth: u32,
+    /// `log2` of the sampling rate, see [`Self::with_sampling`]. `0` means no sampling.
+    sample_log2: u8,
+    /// Whether to draw the substream index from a middle slice of the hash rather than
+    /// the top bits, see [`Self::with_split_index_region`].
+    split_index: bool,
+    /// Ceiling on `t` set by [`Self::with_max_scale`]; `None` means unbounded.
+    max_t: Option<u32>,
+    /// Set once an insert would have rescaled past `max_t`, see [`Self::at_capacity`].
+    at_capacity: bool,
+    /// Number of rescales performed so far, see [`Self::metrics`].
+    #[cfg(feature = "metrics")]
+    rescales: u32,
+    /// Number of [`Self::merge`]/[`Self::merge_detailed`] calls that hit
+    /// [`MergeOutcome::Discarded`] because `t` differed by more than the ladder can
+    /// bridge, see [`Self::discard_count`].
+    #[cfg(feature = "metrics")]
+    discards: u32,
+    /// Total number of elements seen, including duplicates, see [`Self::total_inserts`].
+    #[cfg(feature = "track-inserts")]
+    total_inserts: u64,
+    /// Bottom-`MINHASH_K` sample of every inserted hash, sorted ascending and
+    /// `u64::MAX`-padded, feeding [`Self::minhash_jaccard`]'s KMV similarity estimate.
+    #[cfg(feature = "minhash")]
+    minhash: [u64; MINHASH_K],
+    /// Ring buffer of the most recent [`Self::count`] snapshots, one recorded per
+    /// insert, feeding [`Self::estimate_quantile`]. See [`HISTORY_CAPACITY`] for the
+    /// size and eviction policy.
+    #[cfg(feature = "history")]
+    history: [u32; HISTORY_CAPACITY],
+    /// Next slot [`Self::history_record`] writes to, wrapping at [`HISTORY_CAPACITY`].
+    #[cfg(feature = "history")]
+    history_next: usize,
+    /// Whether `history` has wrapped at least once, i.e. every slot holds a real
+    /// snapshot rather than the initial `0` padding.
+    #[cfg(feature = "history")]
+    history_full: bool,
 }
 
-impl<SKETCH: Sketch, H: Default + BuildHasher> Default for HyperTwoBits<SKETCH, H> {
+impl<
+        SKETCH: Sketch,
+        H: Default + BuildHasher,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > Default for HyperTwoBits<SKETCH, H, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
     fn default() -> Self {
         Self {
             hash: H::default(),
             sketch: SKETCH::default(),
+            #[cfg(not(feature = "compact"))]
             count: 0,
             t: 1,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        }
+    }
+}
+
+// `minhash`/`history` are samples derived from what's inserted, not part of the
+// sketch's counted state: `minhash` is populated by `insert_hash` but not by
+// lower-level entry points like `insert_split` (which never sees the raw hash), and
+// `history` is a FIFO window that depends on *how many* inserts happened, not just
+// which elements were seen, so two sketches with identical counted state can
+// legitimately end up with different `minhash`/`history` contents depending on which
+// entry points fed them and how many times. Excluded here so equality/hashing still
+// reflect only the state that determines `count`/estimates, matching the derived impl
+// used when both features are off.
+#[cfg(any(feature = "minhash", feature = "history"))]
+impl<
+        SKETCH: Sketch + PartialEq,
+        HASH: BuildHasher + PartialEq,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > PartialEq
+    for HyperTwoBits<SKETCH, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.sketch == other.sketch
+            && self.active_count() == other.active_count()
+            && self.t == other.t
+            && self.merge_depth == other.merge_depth
+            && self.sample_log2 == other.sample_log2
+            && self.split_index == other.split_index
+            && self.max_t == other.max_t
+            && self.at_capacity == other.at_capacity
+            && {
+                #[cfg(feature = "metrics")]
+                {
+                    self.rescales == other.rescales && self.discards == other.discards
+                }
+                #[cfg(not(feature = "metrics"))]
+                {
+                    true
+                }
+            }
+            && {
+                #[cfg(feature = "track-inserts")]
+                {
+                    self.total_inserts == other.total_inserts
+                }
+                #[cfg(not(feature = "track-inserts"))]
+                {
+                    true
+                }
+            }
+    }
+}
+
+#[cfg(any(feature = "minhash", feature = "history"))]
+impl<
+        SKETCH: Sketch + Eq,
+        HASH: BuildHasher + Eq,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > Eq for HyperTwoBits<SKETCH, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+}
+
+#[cfg(any(feature = "minhash", feature = "history"))]
+impl<
+        SKETCH: Sketch + Hash,
+        HASH: BuildHasher + Hash,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > Hash for HyperTwoBits<SKETCH, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+        self.sketch.hash(state);
+        self.active_count().hash(state);
+        self.t.hash(state);
+        self.merge_depth.hash(state);
+        self.sample_log2.hash(state);
+        self.split_index.hash(state);
+        self.max_t.hash(state);
+        self.at_capacity.hash(state);
+        #[cfg(feature = "metrics")]
+        {
+            self.rescales.hash(state);
+            self.discards.hash(state);
+        }
+        #[cfg(feature = "track-inserts")]
+        {
+            self.total_inserts.hash(state);
         }
     }
 }
 
-impl<HASH: BuildHasher + Default, BITS: Sketch> HyperTwoBits<BITS, HASH> {
+impl<
+        HASH: BuildHasher,
+        BITS: Sketch,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > HyperTwoBits<BITS, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+    #[inline]
+    /// Returns the number of active substreams, from the cached `count` field, or
+    /// recomputed via [`Sketch::count`] under the `compact` feature -- see the field's
+    /// own doc comment for the space/time tradeoff. Every internal read of the active
+    /// count goes through here so the two builds stay behaviorally identical.
+    fn active_count(&self) -> u32 {
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count
+        }
+        #[cfg(feature = "compact")]
+        {
+            self.sketch.count()
+        }
+    }
+
+    #[inline]
+    /// Applies one [`Sketch::decrement`] step to `self.sketch`, updating the cached
+    /// `count` field to match -- or, under `compact`, just discarding the value
+    /// `decrement` returns, since [`Self::active_count`] recomputes it on demand there.
+    fn apply_decrement(&mut self) {
+        let decremented = self.sketch.decrement();
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = decremented;
+        }
+        #[cfg(feature = "compact")]
+        {
+            let _ = decremented;
+        }
+    }
+}
+
+impl<
+        HASH: BuildHasher + Default,
+        BITS: Sketch,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > HyperTwoBits<BITS, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
     const ALPHA: f64 = 0.988;
+
     #[must_use]
     /// Creates a new `HyperTwoBits` counter with specified hasher and bitset,
     /// use `HyperTwoBits::default()` for default values.
@@ -41,55 +307,711 @@ impl<HASH: BuildHasher + Default, BITS: Sketch> HyperTwoBits<BITS, HASH> {
         Self {
             hash: HASH::default(),
             sketch: BITS::default(),
+            #[cfg(not(feature = "compact"))]
             count: 0,
             t: 1,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        }
+    }
+
+    #[must_use]
+    /// Returns a copy of this counter that will never rescale `t` past `max_t`: once an
+    /// insert would need to grow `t` beyond it, it's discarded instead — the sketch
+    /// keeps its exact current state and [`Self::at_capacity`] switches to `true` — so
+    /// every insert after the cap is hit is a no-op rather than letting `count` creep
+    /// all the way up to `BITS::STREAMS` (which would make [`Self::count`]'s `beta`
+    /// hit exactly zero, blowing up the estimate instead of merely capping it). This
+    /// trades further growth for a hard, predictable ceiling on `t` (and thus the
+    /// sketch's own bit width). Past that point [`Self::count`] undercounts the true
+    /// cardinality. Useful for fixed-accuracy deployments that need a guaranteed
+    /// behavior ceiling regardless of input volume.
+    pub fn with_max_scale(mut self, max_t: u32) -> Self {
+        self.max_t = Some(max_t);
+        self
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns whether an insert has hit the [`Self::with_max_scale`] cap and been
+    /// dropped rather than rescaling past it. Once `true`, [`Self::count`] is an
+    /// undercount of the true cardinality until the cap is raised (there's no way to
+    /// un-set this short of rebuilding the counter, since the dropped inserts can't be
+    /// recovered).
+    pub fn at_capacity(&self) -> bool {
+        self.at_capacity
+    }
+
+    /// Clears the observed bits for a new counting window while keeping the current
+    /// scale `t`: zeroes the sketch and `count`, but leaves `t` (and configuration like
+    /// [`Self::with_max_scale`]'s cap, [`Self::with_sampling`]'s rate, and
+    /// [`Self::with_split_index_region`]) untouched. Also clears [`Self::at_capacity`]
+    /// and [`Self::merge_depth`], since both describe the just-cleared sketch's state
+    /// and would otherwise wrongly carry over into the new window.
+    ///
+    /// Useful for sliding-window counting at a known high-cardinality regime: starting
+    /// a fresh window from `t = 1` via a new counter would force it back through every
+    /// early rescale before reaching useful resolution again, churn this avoids by
+    /// keeping the scale the previous window had already grown into.
+    pub fn clear_keep_scale(&mut self) {
+        self.sketch = BITS::default();
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = 0;
+        }
+        self.at_capacity = false;
+        self.merge_depth = 0;
+    }
+
+    /// Feeds `sample` through the counter to let it rescale up to a fitting `t`, then
+    /// calls [`Self::clear_keep_scale`] so ingestion of the real stream proceeds from
+    /// that calibrated scale instead of `t`'s default starting value of `1`. This
+    /// reduces early rescale churn for streaming ingestion when a representative sample
+    /// of the eventual cardinality is available up front but an exact count (which
+    /// [`Self::with_capacity_hint`] would want) isn't.
+    ///
+    /// `sample` is consumed only for calibration: none of it survives in the counter
+    /// afterward, so calibrating and then inserting the same sample again is not
+    /// double-counting.
+    pub fn calibrate<V: std::hash::Hash>(&mut self, sample: &[V]) {
+        for v in sample {
+            self.insert(v);
+        }
+        self.clear_keep_scale();
+    }
+
+    /// Replaces this counter's hash seed with `new_seed` and resets counting: the
+    /// sketch, `count`, and `t` are all cleared, since substream values computed under
+    /// the old seed are meaningless the moment the hash function they depend on
+    /// changes. Builder configuration ([`Self::with_max_scale`]'s cap,
+    /// [`Self::with_sampling`]'s rate, [`Self::with_split_index_region`]) is untouched,
+    /// since none of it is seed-dependent.
+    ///
+    /// For privacy-sensitive telemetry that periodically rotates its hash seed so a
+    /// sketch from before rotation can't be correlated with one from after by an
+    /// observer who recovers an old seed. This is deliberately the opposite of
+    /// [`Self::merge`], which requires two sketches to already share a seed --
+    /// rotating one intentionally makes it unmergeable with its own pre-rotation self.
+    pub fn rotate_seed(&mut self, new_seed: u64)
+    where
+        HASH: Seedable,
+    {
+        self.hash.reseed(new_seed);
+        self.sketch = BITS::default();
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = 0;
+        }
+        self.t = 1;
+        self.merge_depth = 0;
+        self.at_capacity = false;
+        #[cfg(feature = "metrics")]
+        {
+            self.rescales = 0;
+            self.discards = 0;
+        }
+        #[cfg(feature = "track-inserts")]
+        {
+            self.total_inserts = 0;
+        }
+    }
+
+    #[must_use]
+    /// Builds a counter that starts at scale `t` and never rescales away from it,
+    /// unlike [`Self::with_max_scale`] which still lets `t` grow up to its cap through
+    /// the normal series of rescales. Growing into a cap is itself order-dependent:
+    /// which elements get hashed against the smaller pre-rescale `t` versus the larger
+    /// post-rescale `t` depends on arrival order, since a rescale is only triggered
+    /// once enough *already-inserted* elements have activated substreams. Starting
+    /// fixed at `t` from the very first insert removes that source of
+    /// order-dependence entirely: every element is always hashed against the same
+    /// `t`, so two fixed-scale counters fed the same multiset of elements in different
+    /// orders end up in identical states, which plain [`Self::new`] cannot promise.
+    ///
+    /// This bounds the representable cardinality the same way [`Self::with_max_scale`]
+    /// does: once substreams saturate, [`Self::at_capacity`] switches to `true` and
+    /// further inserts become no-ops, so pick `t` generously for the cardinalities you
+    /// expect. Past that saturation point, order-independence is lost again — which
+    /// elements arrived before the sketch filled up depends on order — so this
+    /// guarantee only holds below the cap.
+    pub fn fixed_scale(t: u32) -> Self {
+        let mut sketch = Self::new();
+        sketch.t = t;
+        sketch.max_t = Some(t);
+        sketch
+    }
+
+    #[must_use]
+    /// Configures hash-based sampling: only 1-in-`2^rate_log2` inserts, selected by a
+    /// fixed pattern in an independently mixed copy of the hash, are actually
+    /// processed by [`Self::insert`] and [`Self::insert_hash`]; [`Self::count`] scales
+    /// the estimate back up to compensate. We can't match the pattern against `hash`'s
+    /// own low bits, since those feed the `trailing_ones` rank ladder directly and
+    /// forcing them to a fixed value would stop any substream from ever advancing.
+    /// This trades variance for reduced work on extremely high-volume streams, and the
+    /// bias becomes pronounced once the true cardinality approaches the sampling rate
+    /// itself, since small counts round away entirely.
+    pub fn with_sampling(mut self, rate_log2: u8) -> Self {
+        self.sample_log2 = rate_log2;
+        self
+    }
+
+    #[must_use]
+    /// Draws the substream index from a slice in the middle of the hash instead of the
+    /// top `64 - BITS::IDX_SHIFT` bits [`Self::new`] uses by default. At large `M` the
+    /// default scheme's index region sits right against the rank region's own top edge;
+    /// this instead carves the index out of the middle third of the hash, so the index
+    /// no longer needs to be drawn from whichever bits happen to sit at the very top —
+    /// useful if [`Self::hasher_quality_sample`] finds a hasher's high bits are weaker
+    /// than its low ones. With a well-mixed 64-bit hash this doesn't add rank
+    /// resolution — [`Self::insert_hash`]'s `trailing_ones` only ever reads a
+    /// contiguous run starting at bit 0, so carving the index out of the middle can only
+    /// match or shrink that run, never extend it — but it moves the index away from the
+    /// hash's top bits without giving up the low ones entirely.
+    pub fn with_split_index_region(mut self) -> Self {
+        self.split_index = true;
+        self
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Splits `hash` into a `(stream, rank_hash)` pair, honoring
+    /// [`Self::with_split_index_region`]. This is the single place that interprets
+    /// [`Self::split_index`]; [`Self::insert_hash`], [`Self::insert_array`] (and so
+    /// [`Self::insert2`]/[`Self::insert4`]/[`Self::insert_chunk`], which forward to
+    /// it), [`Self::maybe_contains`] and [`Self::hasher_quality_sample`] all go
+    /// through it so they stay consistent with each other, even though
+    /// [`Self::insert_array`] otherwise duplicates the rest of
+    /// [`Self::insert_split`]'s ladder inline for micro-batching reasons.
+    fn split_hash(&self, hash: u64) -> (u32, u64) {
+        if self.split_index {
+            let idx_low = BITS::IDX_SHIFT / 2;
+            let index_mask = u64::from(BITS::STREAMS - 1);
+            let stream = ((hash >> idx_low) & index_mask) as u32;
+            let rank_hash = hash & !(index_mask << idx_low);
+            debug_assert!(stream < BITS::STREAMS);
+            (stream, rank_hash)
+        } else {
+            let stream = (hash >> BITS::IDX_SHIFT) as u32;
+            let rank_hash = hash & BITS::HASH_MASK;
+            (stream, rank_hash)
+        }
+    }
+
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )]
+    /// Creates a new counter with `t` initialized to the scale appropriate for an
+    /// anticipated `expected` cardinality, i.e. `log2(expected / BITS::STREAMS)`, rather
+    /// than starting at `t = 1` and rescaling up to that regime one batch at a time.
+    /// Over- or underestimating `expected` only costs some early accuracy while the
+    /// count catches up to the hinted scale; it never affects correctness.
+    pub fn with_capacity_hint(expected: u64) -> Self {
+        let mut htb = Self::new();
+        if expected > u64::from(BITS::STREAMS) {
+            let hinted_t = (expected as f64 / f64::from(BITS::STREAMS)).log2().floor() as u32;
+            htb.t = hinted_t.max(1);
+        }
+        htb
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )]
+    /// Seeds this counter's scale from a [`crate::hbb64::HyperBitBit64`] estimate, for
+    /// teams migrating off HBB64 who want a fresh `HyperTwoBits` to build on top of an
+    /// existing HBB64 aggregate instead of starting cold.
+    ///
+    /// **This is lossy.** HBB64 exposes no per-substream state to reconstruct exactly,
+    /// only its scalar estimate, so this inverts [`Self::count`]'s own formula to pick
+    /// the smallest `t` and a substream fill count that reproduce `hbb`'s estimate,
+    /// growing `t` the same way [`Self::insert_split`] would if the fill needed at the
+    /// current `t` got too close to full. It doesn't transfer which elements HBB64 had
+    /// actually seen, so the same element inserted into both counters before absorbing
+    /// would be double-counted here. Call this once, on a fresh counter, before any
+    /// `insert`s of your own — those still count correctly on top of the seeded scale
+    /// afterward.
+    pub fn absorb_estimate_from<H: std::hash::Hasher + Default>(
+        &mut self,
+        hbb: &crate::hbb64::HyperBitBit64<H>,
+    ) {
+        let estimate = hbb.count() as f64;
+        let m = f64::from(BITS::STREAMS);
+        let threshold = Self::ALPHA * m;
+
+        let mut t = 1u32;
+        let mut fill = m * (1.0 - (-estimate / (f64::from(t).exp2() * m)).exp());
+        while fill > threshold && t < 63 {
+            t += 1;
+            fill = m * (1.0 - (-estimate / (f64::from(t).exp2() * m)).exp());
+        }
+
+        let fill_count = fill.round() as u32;
+        for stream in 0..fill_count {
+            self.sketch.set(stream, 1);
+        }
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = fill_count;
         }
+        self.t = t;
     }
 
-    /// Merges another `HyperTwoBits` counter into this one
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    /// Hashes `0..samples` with this counter's own hasher and scores how well the
+    /// resulting `(stream, rank)` split — the same split [`Self::insert_hash`] performs
+    /// — satisfies the assumptions `HyperTwoBits`'s accuracy relies on: streams spread
+    /// evenly across `BITS::STREAMS` (checked via a chi-square goodness-of-fit against
+    /// a uniform distribution), and the low bits that seed `rank` independently
+    /// balanced around 50/50 (checked via bit-balance, since a hasher with a stuck or
+    /// correlated bit there skews every rank towards the same value). Returns a score
+    /// in `0.0..=1.0`, where `1.0` means both are indistinguishable from ideal and
+    /// values near `0.0` mean this counter's hasher will bias every estimate it
+    /// produces (e.g. a hasher that returns a constant value).
+    ///
+    /// Meant to be run once at construction to catch a bad `HASH` choice early, not on
+    /// a hot path: `samples` in the low thousands is enough to catch a badly broken
+    /// hasher, but this is a smoke test, not a cryptographic audit.
+    pub fn hasher_quality_sample(&self, samples: usize) -> f64 {
+        let mut stream_histogram = vec![0u64; BITS::STREAMS as usize];
+        let mut rank_bit_ones = [0u64; 8];
+
+        for i in 0..samples as u64 {
+            let hash = self.hash.hash_one(i);
+            let (stream, rank_bits) = self.split_hash(hash);
+            stream_histogram[stream as usize] += 1;
+
+            for (bit, ones) in rank_bit_ones.iter_mut().enumerate() {
+                if rank_bits & (1 << bit) != 0 {
+                    *ones += 1;
+                }
+            }
+        }
+
+        let n = samples as f64;
+        let stream_uniformity = chi_square_fit_score(
+            stream_histogram
+                .iter()
+                .map(|&observed| (observed as f64, n / f64::from(BITS::STREAMS))),
+        );
+        // Bit balance, not chi-square: with only two outcomes per bit, a chi-square
+        // statistic caps out well short of flagging "this bit never changes" as bad, so
+        // score each bit directly by how far its 1-fraction sits from an even 50/50.
+        let rank_bit_balance = rank_bit_ones
+            .iter()
+            .map(|&ones| 1.0 - (2.0 * (ones as f64 / n) - 1.0).abs())
+            .sum::<f64>()
+            / rank_bit_ones.len() as f64;
+
+        f64::midpoint(stream_uniformity, rank_bit_balance)
+    }
+
+    /// Merges another `HyperTwoBits` counter into this one. In debug builds, panics via
+    /// `debug_assert!` if `other` is discarded outright because its `t` is too far from
+    /// `self`'s to bridge (see [`Self::merge_detailed`]) -- this is a silent accuracy
+    /// killer in a sharded pipeline (e.g. genomics data parallelized across many
+    /// workers) if the caller never checks for it, so debug builds surface it loudly
+    /// during development while release builds stay silent and just drop `other`, as
+    /// before. Either way the drop is also tallied, see [`Self::discard_count`].
+    ///
+    /// To keep shards mergeable, partition work so that no two shards can drift by more
+    /// than `RESCALE_STEP * 2` rescale steps in `t` before merging -- e.g. merge
+    /// incrementally as shards complete instead of accumulating a large backlog of
+    /// unmerged shards, or bound how much data any single shard processes before its
+    /// first merge.
+    /// # Panics
+    /// If hasheres are seeded as that prevents merging, or (debug builds only) if
+    /// `other` is discarded because `t` differs too much to bridge.
+    pub fn merge(&mut self, other: Self) {
+        let outcome = self.merge_detailed(other);
+        debug_assert!(
+            outcome != MergeOutcome::Discarded,
+            "merge discarded `other` outright: its `t` was too far from `self`'s to \
+             bridge; see `HyperTwoBits::merge`'s docs for partitioning advice"
+        );
+    }
+
+    /// Merges another `HyperTwoBits` counter into this one, like [`Self::merge`], but
+    /// returns which case the merge took. This surfaces the otherwise-silent case where
+    /// `t` differs by more than 8 and `other` is discarded entirely, which can be
+    /// surprising in a distributed merge if left unnoticed.
     /// # Panics
     /// If hasheres are seeded as that prevents merging
-    pub fn merge(&mut self, mut other: Self) {
+    pub fn merge_detailed(&mut self, mut other: Self) -> MergeOutcome {
         assert_eq!(
             self.hash.hash_one(42),
             other.hash.hash_one(42),
             "Hashers must be the same, can not merge"
         );
-        // The paper asks for actions if the sketch is "nearly full", this is a very loose definition
-        // we will assume 99% if substreams set is "nearly full"
+        // The paper asks for actions if the sketch is "nearly full", this is a very loose
+        // definition; we use `Self::merge_full_fraction` ("nearly full" configurable via
+        // `MERGE_FULL_PER_MILLE`, `990` i.e. 99% by default).
         #[allow(
             clippy::cast_lossless,
             clippy::cast_sign_loss,
             clippy::cast_possible_truncation
         )]
-        let threshold = const { (0.99 * (BITS::STREAMS as f64)) as u32 };
+        let threshold = const { (Self::merge_full_fraction() * BITS::STREAMS as f64) as u32 };
         // for simplicity we ensure that `self` is always the larger sketch
         if other.t > self.t {
             std::mem::swap(self, &mut other);
         }
 
-        // If the values of T differ by 8 or more, use the larger value and its sketches.
-        if self.t - other.t > 8 {
-            return;
+        // If the values of T differ by more than the ladder can bridge (2 rescale
+        // steps), use the larger value and its sketches.
+        if self.t - other.t > RESCALE_STEP * 2 {
+            #[cfg(feature = "metrics")]
+            {
+                self.discards += 1;
+            }
+            return MergeOutcome::Discarded;
         }
         // we pre-compute if self.t == other.t so we can do the decrement below before handling
         // the other cases
         let same = self.t == other.t;
         // We now only have the first and third case left, so we can handle the decrement
-        if self.count >= threshold {
-            self.count = self.sketch.decrement();
-            self.t += 4;
+        if self.active_count() >= threshold {
+            let next_t = self.t + RESCALE_STEP * DECREMENT_STEPS;
+            if self.max_t.is_some_and(|max_t| next_t > max_t) {
+                self.at_capacity = true;
+            } else {
+                for _ in 0..DECREMENT_STEPS {
+                    self.apply_decrement();
+                }
+                self.t = next_t;
+                #[cfg(feature = "metrics")]
+                {
+                    self.rescales += 1;
+                }
+            }
         }
 
-        if same {
+        let outcome = if same {
             // Merg sketches
             self.sketch.merge(&other.sketch);
+            MergeOutcome::Merged
         } else {
             // merge the high bits of other into the low bits of self
             self.sketch.merge_high_into_lo(&other.sketch);
-        }
+            MergeOutcome::MergedHighIntoLo
+        };
+        self.merge_depth += 1;
         // update the count
-        self.count = self.sketch.count();
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = self.sketch.count();
+        }
+        outcome
+    }
+
+    /// Like [`Self::merge`], but bridges a `t` gap by repeatedly re-scaling (via
+    /// [`Sketch::decrement`]) the lagging sketch until its `t` matches `self`'s, then
+    /// OR-merging the two sketches directly -- rather than [`Self::merge_detailed`]'s
+    /// single [`Sketch::merge_high_into_lo`] cascade, which folds in one rescale step's
+    /// worth of bits per call and is only exact for a `t` gap of exactly
+    /// `RESCALE_STEP`. For the larger gaps this crate still allows (up to
+    /// `RESCALE_STEP * 2`), that single fold leaves the lagging sketch's bits
+    /// misaligned with `self`'s ladder tiers instead of stepping through each tier in
+    /// turn. Repeatedly rescaling the lagging sketch all the way up to `self.t` first
+    /// keeps every ladder step aligned, at the cost of up to
+    /// `RESCALE_STEP * 2 / (RESCALE_STEP * DECREMENT_STEPS)` extra [`Sketch::decrement`]
+    /// passes over `other`'s data compared to [`Self::merge`].
+    ///
+    /// Returns [`MergeOutcome::Merged`] once `other` has been rescaled up to `self`'s
+    /// `t` and OR-merged in, or [`MergeOutcome::Discarded`] if the gap exceeds
+    /// `RESCALE_STEP * 2` -- this never returns [`MergeOutcome::MergedHighIntoLo`],
+    /// since it never takes that approximate path.
+    /// # Panics
+    /// If hasheres are seeded as that prevents merging (see [`Self::merge`]).
+    pub fn merge_rescaled(&mut self, mut other: Self) -> MergeOutcome {
+        assert_eq!(
+            self.hash.hash_one(42),
+            other.hash.hash_one(42),
+            "Hashers must be the same, can not merge"
+        );
+        // for simplicity we ensure that `self` is always the larger sketch
+        if other.t > self.t {
+            std::mem::swap(self, &mut other);
+        }
+
+        if self.t - other.t > RESCALE_STEP * 2 {
+            #[cfg(feature = "metrics")]
+            {
+                self.discards += 1;
+            }
+            return MergeOutcome::Discarded;
+        }
+
+        #[allow(
+            clippy::cast_lossless,
+            clippy::cast_sign_loss,
+            clippy::cast_possible_truncation
+        )]
+        let threshold = const { (Self::merge_full_fraction() * BITS::STREAMS as f64) as u32 };
+        if self.active_count() >= threshold {
+            let next_t = self.t + RESCALE_STEP * DECREMENT_STEPS;
+            if self.max_t.is_some_and(|max_t| next_t > max_t) {
+                self.at_capacity = true;
+            } else {
+                for _ in 0..DECREMENT_STEPS {
+                    self.apply_decrement();
+                }
+                self.t = next_t;
+                #[cfg(feature = "metrics")]
+                {
+                    self.rescales += 1;
+                }
+            }
+        }
+
+        while other.t < self.t {
+            for _ in 0..DECREMENT_STEPS {
+                other.apply_decrement();
+            }
+            other.t += RESCALE_STEP * DECREMENT_STEPS;
+        }
+
+        self.sketch.merge(&other.sketch);
+        self.merge_depth += 1;
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = self.sketch.count();
+        }
+        MergeOutcome::Merged
+    }
+
+    /// Merges `other` into this counter without consuming it, like [`Self::merge`] but
+    /// taking `other` by reference. Costs a clone of `other`, so prefer [`Self::merge`]
+    /// when you can afford to consume the input.
+    /// # Panics
+    /// If hasheres are seeded as that prevents merging
+    pub fn merge_ref(&mut self, other: &Self) -> MergeOutcome
+    where
+        Self: Clone,
+    {
+        self.merge_detailed(other.clone())
+    }
+
+    /// Merges every sketch in `others` into this one, in order, without consuming the
+    /// slice. This is the ergonomic counterpart to repeatedly calling [`Self::merge`]
+    /// when a reducer holds a `Vec<Self>` it wants to fold into its first element
+    /// without draining it. `t` alignment across the batch is handled the same way as
+    /// any other pairwise merge, one [`Self::merge_ref`] call at a time.
+    /// # Panics
+    /// If hasheres are seeded as that prevents merging
+    pub fn merge_from_slice(&mut self, others: &[Self])
+    where
+        Self: Clone,
+    {
+        for other in others {
+            self.merge_ref(other);
+        }
+    }
+
+    /// Merges `other` into this counter even though it uses a different substream
+    /// count, by first downsampling `other`'s sketch down to this counter's
+    /// `BITS::STREAMS` and then running an ordinary [`Self::merge`]. This lets a
+    /// heterogeneous fleet of shards (some at `M4096`, some at `M1024`) still combine
+    /// into one accumulator, as long as the accumulator (`self`) is sized at the
+    /// *smaller* of the two `M`s.
+    ///
+    /// Downsampling folds each group of `OtherM::STREAMS / BITS::STREAMS` adjacent
+    /// `other` substreams into one substream here, the same way [`Sketch::merge`] folds
+    /// two same-sized sketches together (bitwise OR of their value planes, not a
+    /// per-group max). Precision after this call is bounded by `self`'s smaller `M`:
+    /// the extra resolution `other` held is lost the moment its substreams are folded
+    /// together, exactly as if `other` had been built at `BITS::M` from the start.
+    ///
+    /// Assumes both sketches draw their substream index from the default (non-split)
+    /// hash region: with [`Self::with_split_index_region`] enabled, the bits chosen for
+    /// the index no longer line up across differing `M`, so the substream groupings
+    /// this method assumes silently stop matching what `other`'s hasher actually used.
+    /// # Panics
+    /// If `OtherM::STREAMS` isn't a multiple of `BITS::STREAMS`, or if the hashers were
+    /// seeded differently (see [`Self::merge`]).
+    pub fn merge_any<OtherM: Sketch>(
+        &mut self,
+        other: &HyperTwoBits<OtherM, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>,
+    ) where
+        HASH: Clone,
+    {
+        assert_eq!(
+            self.hash.hash_one(42),
+            other.hash.hash_one(42),
+            "Hashers must be the same, can not merge"
+        );
+        assert!(
+            OtherM::STREAMS >= BITS::STREAMS && OtherM::STREAMS % BITS::STREAMS == 0,
+            "merge_any requires `self`'s M to divide `other`'s M, with `other`'s M \
+             the larger of the two"
+        );
+        debug_assert!(
+            !self.split_index && !other.split_index,
+            "merge_any assumes both sketches use the default (non-split) index region"
+        );
+
+        let ratio = OtherM::STREAMS / BITS::STREAMS;
+        let mut downsampled = BITS::default();
+        for small_stream in 0..BITS::STREAMS {
+            let mut value = 0u8;
+            for offset in 0..ratio {
+                value |= other.sketch.val(small_stream * ratio + offset);
+            }
+            downsampled.set(small_stream, value);
+        }
+
+        let count = downsampled.count();
+        #[cfg(feature = "compact")]
+        let _ = count;
+        self.merge(HyperTwoBits {
+            hash: self.hash.clone(),
+            sketch: downsampled,
+            #[cfg(not(feature = "compact"))]
+            count,
+            t: other.t,
+            merge_depth: other.merge_depth,
+            sample_log2: other.sample_log2,
+            split_index: other.split_index,
+            max_t: other.max_t,
+            at_capacity: other.at_capacity,
+            #[cfg(feature = "metrics")]
+            rescales: other.rescales,
+            #[cfg(feature = "metrics")]
+            discards: other.discards,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: other.total_inserts,
+            #[cfg(feature = "minhash")]
+            minhash: other.minhash,
+            #[cfg(feature = "history")]
+            history: other.history,
+            #[cfg(feature = "history")]
+            history_next: other.history_next,
+            #[cfg(feature = "history")]
+            history_full: other.history_full,
+        });
+    }
+
+    #[must_use]
+    /// Computes the cardinality estimate a real [`Self::merge`] with `other` would
+    /// produce — including its `t`-alignment and discard rules — without mutating
+    /// `self` or `other`. Useful for a cost-based decision (e.g. whether merging two
+    /// shards is worth it) before committing to the real, consuming merge.
+    ///
+    /// Unlike [`Self::union_count_many`], this doesn't require `self` and `other` to
+    /// already share the same `t`: it models the exact lossy behavior [`Self::merge`]
+    /// falls back to otherwise, including a possible [`MergeOutcome::Discarded`].
+    ///
+    /// This clones `self` to run the real merge logic on a scratch copy; prefer
+    /// [`Self::merge`] directly when you already know you want the result.
+    pub fn peek_merge_count(&self, other: &Self) -> u64
+    where
+        Self: Clone,
+    {
+        let mut scratch = self.clone();
+        scratch.merge_ref(other);
+        scratch.count()
+    }
+
+    #[must_use]
+    /// Estimates the cardinality of the union of many sketches without constructing an
+    /// intermediate merged `HyperTwoBits`: ORs all aligned register planes across
+    /// `sketches` into a scratch sketch in a single pass, then applies the estimator
+    /// once. This avoids the N-1 temporary `HyperTwoBits` clones that folding via
+    /// [`Self::merge_ref`] one at a time would produce.
+    /// # Panics
+    /// Panics if `sketches` is empty, if any two were built with differently seeded
+    /// hashers, or if any two disagree on `t` — unlike [`Self::merge`], this doesn't
+    /// bridge differing scales, so all shards must already agree on both.
+    pub fn union_count_many(sketches: &[Self]) -> u64 {
+        let first = sketches.first().expect("sketches must not be empty");
+        let mut union = BITS::default();
+        for sketch in sketches {
+            assert_eq!(
+                first.hash.hash_one(42),
+                sketch.hash.hash_one(42),
+                "Hashers must be the same, can not union"
+            );
+            assert_eq!(
+                first.t, sketch.t,
+                "sketches must share the same t to union directly; use merge instead"
+            );
+            union.merge(&sketch.sketch);
+        }
+        DefaultEstimator::estimate(union.count(), first.t, BITS::STREAMS)
+    }
+
+    #[must_use]
+    /// Estimates the total cardinality across `sketches` by summing each one's own
+    /// [`Self::count`] estimate, rather than OR-ing their registers together like
+    /// [`Self::union_count_many`] does. Targets the *disjoint-shard* regime: if every
+    /// shard saw a distinct slice of the keyspace (e.g. sharded by tenant or by time
+    /// window), each shard's estimator is an independent, unbiased sample of its own
+    /// slice, so the sum of estimates approximates the true total.
+    ///
+    /// Every shard here uses the same estimator over the same number of streams, so
+    /// each estimate carries equal variance and the "variance-weighted" combination
+    /// collapses to a plain sum — there's no shard whose estimate deserves more or
+    /// less trust than another's.
+    ///
+    /// If shards instead saw *overlapping* data, summing their estimates double-counts
+    /// the overlap; use [`Self::union_count_many`] (or [`Self::merge`]) for that
+    /// regime, which combines registers before estimating so overlap is naturally
+    /// deduplicated.
+    /// # Panics
+    /// Panics if `sketches` is empty.
+    pub fn combined_estimate(sketches: &[Self]) -> u64 {
+        assert!(!sketches.is_empty(), "sketches must not be empty");
+        sketches.iter().map(Self::count).sum()
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    /// Heuristic bound on the extra relative error merging `other` into `self` would
+    /// introduce, on top of [`Self::expected_error_at`]'s baseline for the combined
+    /// count. Merging is inherently less precise than ingesting everything into one
+    /// sketch directly (tests exercising it use `delta * 2`), so this exists to help
+    /// callers decide whether a merge is worth it or whether re-ingesting the raw data
+    /// is worth the cost instead.
+    ///
+    /// This is a heuristic, not a proven bound: it does not model `merge_high_into_lo`'s
+    /// bit loss exactly, only that error grows with how many rescale tiers `self` and
+    /// `other` are apart (each tier folds together two independently-scaled samples)
+    /// and with how full the fuller of the two sketches already is. A `t` difference
+    /// beyond what [`Self::merge_detailed`] can bridge returns `1.0`, since `other`
+    /// would be discarded outright.
+    pub fn estimate_merge_error(&self, other: &Self) -> f64 {
+        let t_diff = self.t.abs_diff(other.t);
+        if t_diff > RESCALE_STEP * 2 {
+            return 1.0;
+        }
+        let base = Self::expected_error_at(self.count() + other.count());
+        let tier_penalty = 1.0 + f64::from(t_diff) / f64::from(RESCALE_STEP.max(1));
+        let fill_penalty = 1.0
+            + f64::from(self.active_count().max(other.active_count())) / f64::from(BITS::STREAMS);
+        base * tier_penalty * fill_penalty
     }
 
     #[inline]
@@ -100,182 +1022,1952 @@ impl<HASH: BuildHasher + Default, BITS: Sketch> HyperTwoBits<BITS, HASH> {
         self.insert_hash(hash);
     }
 
+    /// Like [`Self::insert`], but makes the [`Self::with_max_scale`]/[`Self::fixed_scale`]
+    /// capacity boundary explicit instead of silently no-oping once
+    /// [`Self::at_capacity`] is `true`. Returns `Ok(true)` if the insert activated a
+    /// previously-unset substream (the same novelty signal
+    /// [`Self::insert_iter_novelty`] reports), `Ok(false)` if it didn't change anything,
+    /// or `Err(InsertError::AtCapacity)` if the sketch can no longer learn at all.
+    ///
+    /// For fixed-accuracy deployments that need to know the moment a sketch stops being
+    /// trustworthy — rather than discovering it later from a suspiciously flat
+    /// [`Self::count`] — prefer this over [`Self::insert`].
+    /// # Errors
+    /// Returns [`InsertError::AtCapacity`] if this sketch has already hit its scale
+    /// cap and can no longer represent new elements.
+    pub fn try_insert<V: std::hash::Hash + ?Sized>(
+        &mut self,
+        value: &V,
+    ) -> Result<bool, InsertError> {
+        if self.at_capacity {
+            return Err(InsertError::AtCapacity);
+        }
+        let before = self.active_count();
+        self.insert(value);
+        Ok(self.active_count() > before)
+    }
+
+    #[inline]
+    /// Inserts `v` as if it had appeared `n` times.
+    ///
+    /// `HyperTwoBits` counts distinct elements, and re-inserting a key it has already
+    /// seen is a no-op, so this is exactly equivalent to a single [`Self::insert`] call
+    /// regardless of `n`. Provided as an explicit, documented entry point for callers
+    /// porting from multiset-style code, so they don't write a surprising
+    /// `for _ in 0..n { htb.insert(v); }` loop expecting it to change the estimate.
+    pub fn insert_n<V: std::hash::Hash + ?Sized>(&mut self, v: &V, n: u64) {
+        self.insert(v);
+        #[cfg(feature = "track-inserts")]
+        {
+            self.total_inserts += n.saturating_sub(1);
+        }
+        #[cfg(not(feature = "track-inserts"))]
+        {
+            let _ = n;
+        }
+    }
+
+    #[inline]
+    /// Inserts a value using `hasher` instead of this counter's own hasher, for this
+    /// insert only.
+    ///
+    /// This is niche: it lets researchers feed the same sketch from different hashers
+    /// to measure how sensitive the estimate is to hash quality. Mixing hashers within
+    /// a sketch invalidates the merge precondition that both sides were seeded
+    /// identically (see [`Self::merge`]), so a sketch built with `insert_with` should
+    /// only be used for experiments, never merged with another sketch.
+    pub fn insert_with<H: BuildHasher>(&mut self, hasher: &H, v: &impl std::hash::Hash) {
+        self.insert_hash(hasher.hash_one(v));
+    }
+
+    #[inline]
+    /// Inserts the pair `(a, b)` as a single composite key, equivalent to
+    /// `self.insert(&(a, b))` -- provided as a named, documented entry point for
+    /// counting distinct pairs (e.g. `(user_id, url)`) without callers having to
+    /// build their own combined key or discover that a tuple reference already works.
+    ///
+    /// Hashing is order-sensitive: `(A, B)`'s `Hash` impl feeds `a` then `b` into the
+    /// same hasher in that order, so `insert_composite(&x, &y)` and
+    /// `insert_composite(&y, &x)` are distinct elements unless `A` and `B` happen to
+    /// hash identically for the values given.
+    pub fn insert_composite<A: std::hash::Hash + ?Sized, B: std::hash::Hash + ?Sized>(
+        &mut self,
+        a: &A,
+        b: &B,
+    ) {
+        self.insert(&(a, b));
+    }
+
     #[inline]
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     /// Inserts a value into the counter
     pub fn insert_hash(&mut self, hash: u64) {
+        if self.sample_log2 > 0 {
+            // Sampling can't just match `hash`'s own low bits: those bits feed
+            // `trailing_ones()` below, so forcing them to a fixed pattern would zero
+            // out the rank ladder entirely. Instead derive an independent selector by
+            // remixing `hash` and match its low bits against the sampling pattern.
+            let selector = hash.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_right(32);
+            if selector & ((1 << self.sample_log2) - 1) != 0 {
+                return;
+            }
+        }
+        #[cfg(feature = "minhash")]
+        self.minhash_insert(hash);
+        let (stream, hash) = self.split_hash(hash);
+        self.insert_split(stream, hash.trailing_ones());
+        #[cfg(feature = "history")]
+        self.history_record();
+    }
+
+    /// Records this counter's current [`Self::count`] into the [`HISTORY_CAPACITY`]-slot
+    /// ring buffer backing [`Self::estimate_quantile`], overwriting the oldest snapshot
+    /// once full.
+    #[cfg(feature = "history")]
+    #[allow(clippy::cast_possible_truncation)]
+    fn history_record(&mut self) {
+        self.history[self.history_next] = self.count() as u32;
+        self.history_next += 1;
+        if self.history_next == HISTORY_CAPACITY {
+            self.history_next = 0;
+            self.history_full = true;
+        }
+    }
+
+    #[must_use]
+    /// Returns the `q`-quantile (`q` in `[0, 1]`) of [`Self::count`] snapshots recorded
+    /// over the most recent [`HISTORY_CAPACITY`] inserts, or `0` if nothing has been
+    /// recorded yet. `q = 0.5` is the median.
+    ///
+    /// This is a diagnostic aid for spotting cardinality spikes in a long-running
+    /// counter, not a precise quantile sketch: it's a fixed-size FIFO window over
+    /// recent snapshots, so it can only answer for however far back
+    /// [`HISTORY_CAPACITY`] reaches, and every insert recording a new snapshot means a
+    /// query only ever sees this sketch's own recent trajectory, not a merged or
+    /// externally-fed history.
+    /// # Panics
+    /// Never in practice: `q` is clamped into `[0, 1]` before use.
+    #[cfg(feature = "history")]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn estimate_quantile(&self, q: f64) -> u64 {
+        let len = if self.history_full {
+            HISTORY_CAPACITY
+        } else {
+            self.history_next
+        };
+        if len == 0 {
+            return 0;
+        }
+        let mut snapshot = self.history;
+        snapshot[..len].sort_unstable();
+        let q = q.clamp(0.0, 1.0);
+        let index = ((q * (len - 1) as f64).round() as usize).min(len - 1);
+        u64::from(snapshot[index])
+    }
+
+    /// Folds `hash` into the bottom-`MINHASH_K` sample backing [`Self::minhash_jaccard`],
+    /// keeping [`Self::minhash`] sorted ascending. A no-op if `hash` is already present
+    /// or isn't smaller than the current largest sampled value.
+    #[cfg(feature = "minhash")]
+    fn minhash_insert(&mut self, hash: u64) {
+        if hash >= self.minhash[MINHASH_K - 1] || self.minhash.contains(&hash) {
+            return;
+        }
+        let pos = self.minhash.partition_point(|&v| v < hash);
+        self.minhash.copy_within(pos..MINHASH_K - 1, pos + 1);
+        self.minhash[pos] = hash;
+    }
+
+    #[must_use]
+    /// Estimates the Jaccard similarity `|A ∩ B| / |A ∪ B|` between the elements
+    /// inserted into `self` and `other`, using the standard KMV (`k`-minimum-values)
+    /// estimator over each sketch's `MINHASH_K`-element bottom-k sample: the
+    /// estimated union's bottom-k is the smallest `MINHASH_K` values across both
+    /// samples, and the fraction of those present in *both* sketches' own samples
+    /// estimates the similarity.
+    ///
+    /// More accurate than the register-based inclusion-exclusion estimate
+    /// (`self.count() + other.count() - union.count()`, divided by the union's count)
+    /// for small overlaps, where that subtraction amplifies `count()`'s own relative
+    /// error; the tradeoff is the extra `8 * MINHASH_K` bytes per sketch this feature
+    /// costs.
+    ///
+    /// Only meaningful when `self` and `other` were built with the same hasher and
+    /// seed (see [`Self::merge`]) -- otherwise the two bottom-k samples aren't drawn
+    /// from comparable hash spaces and the estimate is meaningless.
+    #[cfg(feature = "minhash")]
+    pub fn minhash_jaccard(&self, other: &Self) -> f64 {
+        let mut union: Vec<u64> = self
+            .minhash
+            .iter()
+            .chain(other.minhash.iter())
+            .copied()
+            .filter(|&v| v != u64::MAX)
+            .collect();
+        union.sort_unstable();
+        union.dedup();
+        union.truncate(MINHASH_K);
+
+        if union.is_empty() {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let shared = union
+            .iter()
+            .filter(|v| self.minhash.contains(v) && other.minhash.contains(v))
+            .count() as f64;
+        #[allow(clippy::cast_precision_loss)]
+        let total = union.len() as f64;
+        shared / total
+    }
+
+    #[inline]
+    /// Inserts a raw byte slice, hashing exactly `bytes` with no length prefix or
+    /// terminator -- see [`Self::insert_str`] for why that matters.
+    pub fn insert_bytes(&mut self, bytes: &[u8]) {
+        let mut hasher = self.hash.build_hasher();
+        hasher.write(bytes);
+        self.insert_hash(hasher.finish());
+    }
+
+    #[inline]
+    /// Inserts a `&str` by hashing its UTF-8 bytes directly through
+    /// [`Self::insert_bytes`], instead of going through [`Self::insert`]'s generic
+    /// `Hash` path.
+    ///
+    /// `Hash for str` writes the string's bytes followed by a `0xff` sentinel byte,
+    /// while `Hash for [u8]` writes the slice's length followed by its bytes -- so
+    /// `insert(s)` and `insert(&s.as_bytes())` hash the same logical string
+    /// differently and count it as two distinct elements if both call sites are used
+    /// for what's meant to be the same key. `insert_str`/`insert_bytes` hash only the
+    /// bytes themselves, so mixed call sites that pick whichever one always agree.
+    pub fn insert_str(&mut self, s: &str) {
+        self.insert_bytes(s.as_bytes());
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    /// Hashes each of `values` with this counter's hasher and splits it into a
+    /// `(stream, rank)` pair via [`Self::split_hash`], the same interpretation
+    /// [`Self::insert_hash`] uses, but without inserting anything -- `self` is
+    /// unchanged. Feeding the returned pairs into [`Self::insert_split`] one at a time
+    /// reproduces exactly what inserting `values` directly would have done.
+    ///
+    /// For systems that shard by stream index (e.g. routing elements to `BITS::STREAMS`
+    /// worker partitions before any sketch exists), so the routing decision can be made
+    /// up front from this counter's own hasher and `M` parameters, and the actual
+    /// inserts happen later, elsewhere, or in a different order.
+    pub fn hash_to_splits<V: std::hash::Hash>(&self, values: &[V]) -> Vec<(u32, u32)> {
+        values
+            .iter()
+            .map(|v| {
+                let hash = self.hash.hash_one(v);
+                let (stream, rank_hash) = self.split_hash(hash);
+                (stream, rank_hash.trailing_ones())
+            })
+            .collect()
+    }
+
+    #[inline]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Runs the value ladder for an already-computed `(stream, rank)` pair, bypassing
+    /// the internal hash split. This is the lowest-level insertion primitive, useful
+    /// for research and interop with custom hashing schemes that derive `stream` and
+    /// `rank` differently (e.g. from different bit regions, or a non-hash source).
+    /// # Panics
+    /// Debug-asserts that `stream < BITS::STREAMS`.
+    pub fn insert_split(&mut self, stream: u32, rank: u32) {
+        debug_assert!(stream < BITS::STREAMS, "stream must be < STREAMS");
+        if self.at_capacity {
+            return;
+        }
+        #[cfg(feature = "track-inserts")]
+        {
+            self.total_inserts += 1;
+        }
         let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
+        if rank >= self.t && self.sketch.val(stream) < 1 {
+            #[cfg(not(feature = "compact"))]
+            {
+                self.count += 1;
+            }
             self.sketch.set(stream, 1);
         }
         // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
+        if rank >= self.t + RESCALE_STEP && self.sketch.val(stream) < 2 {
             self.sketch.set(stream, 2);
         }
 
         // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
+        if rank >= self.t + RESCALE_STEP * 2 && self.sketch.val(stream) < 3 {
             self.sketch.set(stream, 3);
         }
 
-        if self.count >= threshold {
-            self.count = self.sketch.decrement();
-            self.t += 4;
+        if self.active_count() >= threshold {
+            let next_t = self.t + RESCALE_STEP * DECREMENT_STEPS;
+            if self.max_t.is_some_and(|max_t| next_t > max_t) {
+                self.at_capacity = true;
+            } else {
+                for _ in 0..DECREMENT_STEPS {
+                    self.apply_decrement();
+                }
+                self.t = next_t;
+                #[cfg(feature = "metrics")]
+                {
+                    self.rescales += 1;
+                }
+            }
         }
     }
 
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Weak "possibly seen" signal: recomputes `v`'s `(stream, rank)` the same way
+    /// [`Self::insert_hash`] would, and returns whether the substream's stored value is
+    /// at least as high as the tier `v`'s rank would have pushed it to.
+    ///
+    /// **This is not a Bloom filter and false positives are frequent.** `BITS::STREAMS`
+    /// substreams are shared across the entire input domain, so many *other* elements
+    /// map to the same stream as `v` and could have set it to a tier at or above the one
+    /// `v`'s own rank demands — `maybe_contains` can't tell `v` apart from them. False
+    /// positives are especially common for low-rank elements, since tier 0 is satisfied
+    /// by `val(stream) >= 0`, which always holds. A `false` result is reliable (that
+    /// substream could never have reached the tier `v` needs), a `true` result is not.
+    pub fn maybe_contains<V: std::hash::Hash + ?Sized>(&self, v: &V) -> bool {
+        let hash = self.hash.hash_one(v);
+        let (stream, rank_hash) = self.split_hash(hash);
+        let rank = rank_hash.trailing_ones();
+        let expected_tier = u8::from(rank >= self.t)
+            + u8::from(rank >= self.t + RESCALE_STEP)
+            + u8::from(rank >= self.t + RESCALE_STEP * 2);
+        self.sketch.val(stream) >= expected_tier
+    }
+
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss
+    )]
+    /// Heuristic estimate, in `[0.0, 1.0]`, of the probability that inserting `v` changed
+    /// this sketch's state. This is a research/debugging aid for accuracy discussions --
+    /// the sketch doesn't retain enough state to answer this exactly after the fact, since
+    /// a substream's stored value only remembers the highest tier *any* element reached,
+    /// not which element reached it.
+    ///
+    /// Recomputes `v`'s `(stream, rank)` the same way [`Self::insert_hash`] would and
+    /// compares the tier that rank demands against the substream's current stored value,
+    /// the same check [`Self::maybe_contains`] makes:
+    /// - If `v`'s rank would raise the substream above its current value, inserting `v`
+    ///   provably would raise it, so this returns `1.0`.
+    /// - Otherwise `v`'s rank only ties or trails the substream's current value, and any
+    ///   of the (unknown, unbounded) other elements hashed to the same substream could
+    ///   have set it there instead of `v`. This returns `1.0 - 2.0.powi(-rank)`, the
+    ///   complement of a random hash's rough odds of reaching at least `rank`: higher
+    ///   ranks are exponentially rarer for a well-distributed hash, so the higher `v`'s
+    ///   own rank, the fewer other elements are even plausible candidates for having
+    ///   matched it -- and the more of the remaining uncertainty this heuristic
+    ///   attributes to `v` itself.
+    ///
+    /// Two calls with the same substream state can disagree about which element "really"
+    /// set it -- this is deliberately an estimate, not a lookup.
+    pub fn influence<V: std::hash::Hash>(&self, v: &V) -> f64 {
+        let hash = self.hash.hash_one(v);
+        let (stream, rank_hash) = self.split_hash(hash);
+        let rank = rank_hash.trailing_ones();
+        let expected_tier = u8::from(rank >= self.t)
+            + u8::from(rank >= self.t + RESCALE_STEP)
+            + u8::from(rank >= self.t + RESCALE_STEP * 2);
+        if expected_tier > self.sketch.val(stream) {
+            return 1.0;
+        }
+        1.0 - 2f64.powi(-(rank as i32))
+    }
+
     #[inline]
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Inserts exactly `N` elements as a single micro-batch: runs the value ladder over
+    /// each element in turn — as a const-generic loop the compiler unrolls for small,
+    /// compile-time-known `N` — then checks the rescale threshold once at the end,
+    /// delaying the count update the same way the hand-written [`Self::insert2`]/
+    /// [`Self::insert4`] do. Generalizes their fixed batch sizes to any `N` a caller
+    /// wants.
+    pub fn insert_array<const N: usize, V: std::hash::Hash>(&mut self, values: &[V; N]) {
+        if self.at_capacity {
+            return;
+        }
+        #[cfg(feature = "track-inserts")]
+        {
+            self.total_inserts += N as u64;
+        }
+        let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
+
+        for v in values {
+            let hash = self.hash.hash_one(v);
+            let (stream, hash) = self.split_hash(hash);
+
+            if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
+                #[cfg(not(feature = "compact"))]
+                {
+                    self.count += 1;
+                }
+                self.sketch.set(stream, 1);
+            }
+            // 2^4
+            if hash.trailing_ones() >= self.t + RESCALE_STEP && self.sketch.val(stream) < 2 {
+                self.sketch.set(stream, 2);
+            }
+
+            // 2^8
+            if hash.trailing_ones() >= self.t + RESCALE_STEP * 2 && self.sketch.val(stream) < 3 {
+                self.sketch.set(stream, 3);
+            }
+        }
+
+        if self.active_count() >= threshold {
+            let next_t = self.t + RESCALE_STEP * DECREMENT_STEPS;
+            if self.max_t.is_some_and(|max_t| next_t > max_t) {
+                self.at_capacity = true;
+            } else {
+                for _ in 0..DECREMENT_STEPS {
+                    self.apply_decrement();
+                }
+                self.t = next_t;
+                #[cfg(feature = "metrics")]
+                {
+                    self.rescales += 1;
+                }
+            }
+        }
+    }
+
+    #[inline]
     /// Inserts 2 elements into the counter for micro batching purposes, note this will delay
     /// the count update to the end
     pub fn insert2<V: std::hash::Hash>(&mut self, v1: &V, v2: &V) {
-        let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
+        self.insert_array(&[v1, v2]);
+    }
 
-        let hash = self.hash.hash_one(v1);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+    #[inline]
+    /// Inserts 4 elements into the counter for micro batching purposes, note this will delay
+    /// the count update to the end
+    pub fn insert4<V: std::hash::Hash>(&mut self, v1: &V, v2: &V, v3: &V, v4: &V) {
+        self.insert_array(&[v1, v2, v3, v4]);
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    #[inline]
+    /// Inserts every element of `chunk`, dispatching to [`Self::insert4`]/
+    /// [`Self::insert2`] for groups of 4/2 and [`Self::insert`] for a trailing 0-3
+    /// remainder, so callers with already-chunked data (e.g. from `chunks_exact(4)`)
+    /// get the micro-batching benefit without hand-writing the dispatch themselves.
+    pub fn insert_chunk<V: std::hash::Hash>(&mut self, chunk: &[V]) {
+        let mut fours = chunk.chunks_exact(4);
+        for four in &mut fours {
+            self.insert4(&four[0], &four[1], &four[2], &four[3]);
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+
+        let mut twos = fours.remainder().chunks_exact(2);
+        for two in &mut twos {
+            self.insert2(&two[0], &two[1]);
         }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+        for v in twos.remainder() {
+            self.insert(v);
         }
+    }
 
-        let hash = self.hash.hash_one(v2);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+    /// Inserts every element of `values`, returning how many previously-inactive
+    /// substreams became active over the whole batch — a proxy for how much new
+    /// information the batch carried. Reported net of any rescale: a rescale's
+    /// decrement can drop the raw active-substream count, but that drop isn't lost
+    /// information being reported as negative, it's just headroom freed up on the
+    /// ladder, so per-element drops are floored at zero rather than allowed to cancel
+    /// out real gains earlier in the batch. Useful for adaptive batching, e.g. backing
+    /// off once a source stops contributing new elements.
+    pub fn insert_slice<V: std::hash::Hash>(&mut self, values: &[V]) -> u32 {
+        let mut gained = 0;
+        for v in values {
+            let before = self.active_count();
+            self.insert(v);
+            gained += self.active_count().saturating_sub(before);
+        }
+        gained
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    /// Inserts every element yielded by `values`, like [`Self::insert_slice`] but over
+    /// any [`IntoIterator`] rather than requiring an in-memory slice. See
+    /// [`Self::insert_slice`] for how the returned delta accounts for rescales.
+    pub fn insert_iter<V: std::hash::Hash>(&mut self, values: impl IntoIterator<Item = V>) -> u32 {
+        let mut gained = 0;
+        for v in values {
+            let before = self.active_count();
+            self.insert(&v);
+            gained += self.active_count().saturating_sub(before);
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+        gained
+    }
+
+    /// Inserts every element yielded by `values`, returning a per-element `bool` marking
+    /// whether that element set a previously-unset substream, i.e. whether `count()`'s
+    /// active-substream tally grew because of it. Ties [`Self::insert_slice`]'s "how much
+    /// new information arrived" delta to individual elements rather than the whole batch,
+    /// for approximate dedup pipelines that want to forward only "new" items downstream
+    /// without keeping an exact `HashSet` of everything seen.
+    ///
+    /// This is an approximation, not exact deduplication, and it is one-sided: a
+    /// genuinely repeated element is always reported as not-novel (the same input always
+    /// hashes to the same substream and rank, so a repeat can never activate anything new
+    /// a first insert didn't already). But a genuinely new element can also be reported as
+    /// not-novel if it hashes into a substream another, unrelated element already
+    /// activated at an equal or higher rank — a false negative, never a false positive.
+    /// Downstream consumers should expect to occasionally drop a few new items, never to
+    /// forward a duplicate as new.
+    pub fn insert_iter_novelty<V: std::hash::Hash>(
+        &mut self,
+        values: impl IntoIterator<Item = V>,
+    ) -> Vec<bool> {
+        let mut novelty = Vec::new();
+        for v in values {
+            let before = self.active_count();
+            self.insert(&v);
+            novelty.push(self.active_count() > before);
         }
+        novelty
+    }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+    #[cfg(feature = "async-stream")]
+    /// Consumes `stream`, inserting every item as it arrives, for async ingestion
+    /// pipelines (log processors, message-bus consumers) that want to feed a sketch
+    /// without blocking. The core crate otherwise stays entirely sync; this is an
+    /// additive, opt-in path built on [`futures::Stream`] so callers aren't forced onto
+    /// a specific async runtime.
+    pub async fn insert_stream<V: std::hash::Hash>(
+        &mut self,
+        stream: impl futures::Stream<Item = V>,
+    ) {
+        futures::pin_mut!(stream);
+        while let Some(item) = futures::StreamExt::next(&mut stream).await {
+            self.insert(&item);
         }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Returns the substream-activation count at which the next insert triggers a
+    /// rescale (`t += 4` and a sketch decrement), i.e. `(ALPHA * BITS::STREAMS) as u32`.
+    /// Exposed for testing and observability around the near-full rescale behavior.
+    pub fn decrement_threshold() -> u32 {
+        const { (Self::ALPHA * BITS::STREAMS as f64) as u32 }
+    }
+
+    #[must_use]
+    /// Returns the fill fraction of `BITS::STREAMS` at which [`Self::merge_detailed`]
+    /// and [`Self::merge_rescaled`] pre-emptively rescale `self` before folding `other`
+    /// in, i.e. `MERGE_FULL_PER_MILLE as f64 / 1000.0`. Exposed for testing and
+    /// observability around the near-full merge rescale behavior; see
+    /// [`Self::decrement_threshold`] for the analogous per-insert threshold.
+    pub const fn merge_full_fraction() -> f64 {
+        MERGE_FULL_PER_MILLE as f64 / 1000.0
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns whether the next insert that activates a currently-unset substream
+    /// could trigger a rescale.
+    pub fn will_rescale(&self) -> bool {
+        self.active_count() + 1 >= Self::decrement_threshold()
+    }
 
-        if self.count >= threshold {
-            self.count = self.sketch.decrement();
-            self.t += 4;
+    #[must_use]
+    #[inline]
+    /// Returns the number of hash bits left for rank after the top `BITS::IDX_SHIFT`
+    /// bits are spent selecting a substream, i.e. `64 - BITS::IDX_SHIFT`.
+    ///
+    /// This bounds how far `trailing_ones()` can walk through the value-ladder tiers:
+    /// a larger `M` needs more bits to select among its substreams, leaving fewer rank
+    /// bits and capping the register value (and thus precision) reachable before those
+    /// bits run out, given a 64-bit hash.
+    ///
+    /// # Examples
+    /// ```
+    /// use hypertwobits::h2b::{HyperTwoBits, M64, M4096};
+    /// assert_eq!(HyperTwoBits::<M64>::new().used_rank_bits(), 6);
+    /// assert_eq!(HyperTwoBits::<M4096>::new().used_rank_bits(), 12);
+    /// ```
+    pub fn used_rank_bits(&self) -> u32 {
+        64 - BITS::IDX_SHIFT
+    }
+
+    #[inline]
+    /// Inserts an ASCII byte string case-insensitively, without allocating a lowercased
+    /// copy: bytes are folded to lowercase into a small stack buffer and fed to the
+    /// hasher in chunks. Useful for high-volume case-insensitive log token counting.
+    pub fn insert_ascii_ci(&mut self, bytes: &[u8]) {
+        let mut hasher = self.hash.build_hasher();
+        let mut chunk = [0u8; 64];
+        for window in bytes.chunks(chunk.len()) {
+            for (dst, &b) in chunk.iter_mut().zip(window) {
+                *dst = b.to_ascii_lowercase();
+            }
+            hasher.write(&chunk[..window.len()]);
         }
+        self.insert_hash(hasher.finish());
+    }
+
+    #[inline]
+    /// Inserts every `width`-byte chunk of `data` as its own key, for counting fixed-
+    /// width binary keys (e.g. columnar data read straight from a memory-mapped file)
+    /// without copying them into owned values first.
+    /// # Panics
+    /// Panics if `width` is `0`, or if `data.len()` is not a multiple of `width`.
+    pub fn insert_fixed_width(&mut self, data: &[u8], width: usize) {
+        assert!(width > 0, "width must be non-zero");
+        assert_eq!(
+            data.len() % width,
+            0,
+            "data length must be a multiple of width"
+        );
+        for key in data.chunks(width) {
+            self.insert(key);
+        }
+    }
+
+    #[inline]
+    /// Populates the counter from an iterator of pre-hashed values, using the same
+    /// batched-decrement strategy as [`Self::insert_hash`]. This is the bulk equivalent of
+    /// repeatedly calling `insert_hash` and is intended for migrating from an exact
+    /// `HashSet<u64>` of hashes without having to iterate in user code.
+    pub fn populate_from_hashes(&mut self, hashes: impl IntoIterator<Item = u64>) {
+        for hash in hashes {
+            self.insert_hash(hash);
+        }
+    }
+
+    #[must_use]
+    /// Builds a counter directly from a precomputed hash array, skipping [`Self::new`]'s
+    /// hasher entirely. Feeding the same `hashes` slice to [`Self::from_hash_stream`],
+    /// [`crate::h3b::HyperThreeBits::from_hash_stream`], and an HLL adapter removes
+    /// hasher choice as a confound when comparing estimators head to head, since all
+    /// three then see byte-identical input regardless of what each would otherwise hash
+    /// values with.
+    pub fn from_hash_stream(hashes: &[u64]) -> Self {
+        let mut sketch = Self::new();
+        sketch.populate_from_hashes(hashes.iter().copied());
+        sketch
     }
 
+    /// Below this many active substreams, [`Self::count`] returns `count` directly
+    /// instead of running it through the log-based formula.
+    const SMALL_CARDINALITY_THRESHOLD: u32 = 8;
+
+    /// returns the estimated count. This function is non destructive
+    /// and can be called multiple times without changing the state of the counter
+    ///
+    /// Below [`Self::SMALL_CARDINALITY_THRESHOLD`] active substreams while `t` is still
+    /// at its initial value of `1`, this returns `count` directly rather than the
+    /// log-based formula below: that formula always applies a `2^t` scale factor, but at
+    /// `t == 1` no rescale has actually happened yet, so it silently doubles an estimate
+    /// that should still be `1x`. With this few elements against `BITS::STREAMS`
+    /// substreams, a collision (two elements landing on the same substream) is
+    /// vanishingly unlikely, so the active-substream count already IS the cardinality.
     #[inline]
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    /// Inserts 4 elements into the counter for micro batching purposes, note this will delay
-    /// the count update to the end
-    pub fn insert4<V: std::hash::Hash>(&mut self, v1: &V, v2: &V, v3: &V, v4: &V) {
-        let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
+    pub fn count(&self) -> u64 {
+        self.count_from(self.active_count())
+    }
+
+    /// [`Self::count`]'s formula, but run against a caller-supplied active-substream
+    /// tally instead of `self.count`. Used by [`Self::jackknife_error`] to see what the
+    /// estimate would have been had one substream never activated, without mutating or
+    /// cloning the sketch.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn count_from(&self, active: u32) -> u64 {
+        if self.t == 1 && active <= Self::SMALL_CARDINALITY_THRESHOLD {
+            return u64::from(active) << self.sample_log2;
+        }
 
-        let hash = self.hash.hash_one(v1);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+        (estimate_cardinality(active, self.t, BITS::STREAMS) as u64) << self.sample_log2
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    #[must_use]
+    #[inline]
+    /// Like [`Self::count`], but returns [`estimate_cardinality`]'s raw `f64` result
+    /// scaled by [`Self::sample_log2`], without [`Self::count`]'s small-cardinality
+    /// special case or the final truncating cast to `u64`. Lets researchers compare
+    /// the log-based formula's output directly against [`Self::count`] to see how much
+    /// of the difference at low cardinality comes from that special case versus the
+    /// formula itself.
+    pub fn count_f64(&self) -> f64 {
+        estimate_cardinality(self.active_count(), self.t, BITS::STREAMS)
+            * f64::from(self.sample_log2).exp2()
+    }
+
+    /// Downward correction [`Self::count_merge_corrected`] applies per merge, as a
+    /// fraction of the raw estimate: bias observed to accumulate near the `t` boundary
+    /// each time two differently-aged sketches are folded together, which is why the
+    /// merge tests hold a merged sketch's error to `delta * 2` rather than `delta`.
+    const MERGE_BIAS_PER_DEPTH: f64 = 0.02;
+
+    #[must_use]
+    #[inline]
+    /// Returns the number of real merges ([`Self::merge`]/[`Self::merge_detailed`])
+    /// folded into this sketch so far. A [`MergeOutcome::Discarded`] merge doesn't touch
+    /// `self`'s data, so it doesn't advance this. Feed this into
+    /// [`Self::count_merge_corrected`] to correct for the bias this sketch's own merge
+    /// history has accumulated.
+    pub fn merge_depth(&self) -> u32 {
+        self.merge_depth
+    }
+
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )]
+    /// Returns [`Self::count`] adjusted downward for the overestimation bias that
+    /// merging accumulates near the `t` boundary: [`Self::merge_detailed`]'s
+    /// `MergedHighIntoLo` case (and, more mildly, a same-`t` merge) can double-count
+    /// substreams the two operands would otherwise have agreed on. The correction
+    /// shrinks the raw estimate by [`Self::MERGE_BIAS_PER_DEPTH`] per merge folded in,
+    /// clamped so it can never invert the estimate. Pass [`Self::merge_depth`] to
+    /// correct for this sketch's actual merge history, or a hypothetical value to model
+    /// a different one.
+    pub fn count_merge_corrected(&self, merge_depth: u32) -> u64 {
+        let factor = (1.0 - Self::MERGE_BIAS_PER_DEPTH * f64::from(merge_depth)).max(0.0);
+        (self.count() as f64 * factor) as u64
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    /// Returns the estimated fraction of `whole` that `self` accounts for, i.e.
+    /// `self.count() / whole.count()`. Returns `0.0` if `whole` is empty, and clamps the
+    /// result to `1.0` since sampling noise can otherwise push a cohort's estimate
+    /// slightly above its whole's.
+    ///
+    /// This assumes `self` counts a genuine subset of what `whole` counts (e.g. a
+    /// cohort sketch fed a subset of the events fed to a global sketch). For two
+    /// sketches whose populations merely overlap without one containing the other,
+    /// this ratio isn't meaningful — estimate their overlap directly instead (e.g. via
+    /// inclusion-exclusion over a union built with [`Self::merge`]).
+    pub fn subset_fraction(&self, whole: &Self) -> f64 {
+        let whole_count = whole.count();
+        if whole_count == 0 {
+            return 0.0;
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+        (self.count() as f64 / whole_count as f64).min(1.0)
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns the raw components [`Self::count`] derives its estimate from — `t`, the
+    /// number of active substreams, and `BITS::STREAMS` — for callers that need to
+    /// apply their own (possibly fixed-point) estimator instead of this crate's
+    /// `f64`-based one, e.g. in a `no_std` environment without libm's `ln`.
+    ///
+    /// # Examples
+    /// ```
+    /// use hypertwobits::h2b::{HyperTwoBits, M256};
+    /// let mut htb = HyperTwoBits::<M256>::new();
+    /// for i in 0..1_000u64 {
+    ///     htb.insert(&i);
+    /// }
+    ///
+    /// let (t, active, m) = htb.count_components();
+    /// let beta = 1.0 - f64::from(active) / f64::from(m);
+    /// let bias = (1.0 / beta).ln();
+    /// let reconstructed = (f64::from(t).exp2() * f64::from(m) * bias) as u64;
+    /// assert_eq!(reconstructed, htb.count());
+    /// ```
+    pub fn count_components(&self) -> (u32, u32, u32) {
+        (self.t, self.active_count(), BITS::STREAMS)
+    }
+
+    #[cfg(feature = "track-inserts")]
+    #[must_use]
+    #[inline]
+    /// Returns the total number of elements processed, including duplicates — unlike
+    /// [`Self::count`], this is an exact running total, not a cardinality estimate.
+    /// Useful for rate monitoring (elements/sec ingested) where a separate counter in
+    /// user code would otherwise duplicate work `insert` is already doing. Elements
+    /// dropped by [`Self::with_sampling`] are not counted, since they never reach the
+    /// ladder. Gated behind the `track-inserts` feature so the counter and its
+    /// increment aren't paid for by callers who only need `count()`.
+    pub fn total_inserts(&self) -> u64 {
+        self.total_inserts
+    }
+
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    #[inline]
+    /// Returns the number of [`Self::merge`]/[`Self::merge_detailed`] calls that hit
+    /// [`MergeOutcome::Discarded`] on this counter, i.e. where `other`'s `t` was too
+    /// far from `self`'s to bridge and `other` was dropped entirely rather than merged.
+    /// A nonzero count here means shards are being partitioned too unevenly to merge
+    /// cleanly -- see [`Self::merge`]'s docs for a recommended partitioning strategy.
+    /// Gated behind the `metrics` feature alongside [`Self::metrics`].
+    pub fn discard_count(&self) -> u32 {
+        self.discards
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns the estimator's current bias-correction factor, `ln(1 / beta)` where
+    /// `beta = 1 - count / m`. [`Self::count`] multiplies this into the raw `2^t * m`
+    /// scale to correct for substreams that saw more than one qualifying element;
+    /// exposed directly for callers analyzing the estimator's behavior.
+    ///
+    /// ```
+    /// use hypertwobits::h2b::{HyperTwoBits, M4096};
+    ///
+    /// let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    /// for i in 0..10_000u64 {
+    ///     htb.insert(&i);
+    /// }
+    /// println!("current bias: {}", htb.current_bias());
+    /// ```
+    pub fn current_bias(&self) -> f64 {
+        let beta = 1.0 - f64::from(self.active_count()) / f64::from(BITS::STREAMS);
+        #[cfg(feature = "fast-math")]
+        {
+            fast_ln(1.0 / beta)
+        }
+        #[cfg(not(feature = "fast-math"))]
+        {
+            (1.0 / beta).ln()
         }
+    }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+    #[must_use]
+    /// Like [`Self::count`], but computes the estimate with a caller-supplied
+    /// [`Estimator`] instead of the built-in formula. Lets users swap in alternative
+    /// estimators (e.g. an Ertl-style bias correction) without this crate having to grow
+    /// a `count_*` method per variant.
+    pub fn count_with<E: Estimator>(&self) -> u64 {
+        E::estimate(self.active_count(), self.t, BITS::STREAMS) << self.sample_log2
+    }
+
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    /// Picks the estimator formula best suited to this sketch's current fill, without
+    /// changing what [`Self::count`] itself returns:
+    ///
+    /// - **Low fill** (`t == 1` and `active <= `[`Self::SMALL_CARDINALITY_THRESHOLD`],
+    ///   i.e. a fill ratio of `SMALL_CARDINALITY_THRESHOLD / BITS::STREAMS` or below):
+    ///   matches [`Self::count`], which already switches to linear counting
+    ///   (`active << sample_log2`) here. At this few active substreams, this crate's
+    ///   own tests show the log-based formula below overshoots badly on sampling
+    ///   noise alone, so there's nothing to improve on in this regime.
+    /// - **Mid range** (up to [`Self::ALPHA`] fill): also matches [`Self::count`]'s
+    ///   `2^t * m * ln(1 / beta)` formula, the regime it's tuned for.
+    /// - **Near saturation** (at or above [`Self::ALPHA`] fill -- the same threshold
+    ///   [`Self::insert_split`] uses to trigger a rescale, reachable here when
+    ///   [`Self::with_max_scale`] holds a sketch at capacity): floors `beta` at
+    ///   `1.0 / BITS::STREAMS` before applying [`Self::count`]'s formula, since
+    ///   `ln(1 / beta)` diverges as `beta` approaches zero and a single near-full
+    ///   substream count can otherwise blow the estimate up arbitrarily. This caps the
+    ///   estimate at `2^t * m * ln(m)` instead, which is where this method actually
+    ///   improves on [`Self::count`] -- the low and mid regimes are included mainly so
+    ///   callers have one method that behaves sensibly across the full fill range
+    ///   rather than needing to know which regime they're in.
+    pub fn count_adaptive(&self) -> u64 {
+        let fill_ratio = f64::from(self.active_count()) / f64::from(BITS::STREAMS);
+
+        if fill_ratio < Self::ALPHA {
+            return self.count();
         }
 
-        let hash = self.hash.hash_one(v2);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+        let beta = (1.0 - fill_ratio).max(1.0 / f64::from(BITS::STREAMS));
+        #[cfg(feature = "fast-math")]
+        let bias = fast_ln(1.0 / beta);
+        #[cfg(not(feature = "fast-math"))]
+        let bias = (1.0 / beta).ln();
+        let estimate = (f64::from(self.t).exp2() * f64::from(BITS::STREAMS) * bias) as u64;
+        estimate << self.sample_log2
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    #[must_use]
+    /// Models this configuration's expected relative error at a given true `cardinality`,
+    /// so callers can pick `M` to match an existing `HyperLogLog` configuration at equal
+    /// memory without having to run their own benchmark first.
+    ///
+    /// Follows the standard LogLog-family asymptotic relative standard error of
+    /// `1.04 / sqrt(BITS::STREAMS)`, inflated for small cardinalities where fewer than
+    /// one element per substream has landed yet and the estimate hasn't reached that
+    /// asymptote (observed on the corpora under `data/`, e.g. `M4096` staying within 10%
+    /// on the ~35k-word Shakespeare corpus but drifting higher on much smaller prefixes
+    /// of it).
+    pub fn expected_error_at(cardinality: u64) -> f64 {
+        let streams = f64::from(BITS::STREAMS);
+        let asymptotic = 1.04 / streams.sqrt();
+        #[allow(clippy::cast_precision_loss)]
+        let fill_penalty = (streams / cardinality.max(1) as f64).max(1.0);
+        asymptotic * fill_penalty
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    /// Estimates [`Self::count`]'s standard error empirically, via leave-one-substream-out
+    /// jackknife resampling, for callers who'd rather have a data-driven error bar than
+    /// trust [`Self::expected_error_at`]'s asymptotic model on their actual data.
+    ///
+    /// For each substream, recomputes what the estimate would have been had that one
+    /// substream never activated, then combines the `BITS::STREAMS` leave-one-out
+    /// estimates via the standard jackknife variance formula. This is `O(BITS::STREAMS)`
+    /// and touches no hashing, so it's cheap enough to call on demand but not cheap
+    /// enough to run on every insert.
+    pub fn jackknife_error(&self) -> f64 {
+        let n = f64::from(BITS::STREAMS);
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let count = self.active_count();
+        for stream in 0..BITS::STREAMS {
+            let active = if self.sketch.val(stream) == 0 {
+                count
+            } else {
+                count.saturating_sub(1)
+            };
+            let leave_one_out = self.count_from(active) as f64;
+            sum += leave_one_out;
+            sum_sq += leave_one_out * leave_one_out;
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+        let mean = sum / n;
+        let variance = ((n - 1.0) / n) * (sum_sq - n * mean * mean).max(0.0);
+        variance.sqrt()
+    }
+
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )]
+    /// Returns `(low, estimate, high)`, [`Self::count`] bracketed by a confidence
+    /// interval of `estimate * (1 ± z * rse)`, where `rse` is
+    /// [`Self::expected_error_at`]'s modeled relative standard error at this estimate.
+    /// `low` is clamped at `0` so a wide interval on a small estimate can't go negative.
+    ///
+    /// `z` is the number of standard errors to bracket, e.g. `1.96` for a ~95%
+    /// interval under the usual normal approximation; see [`Self::count_ci95`] for that
+    /// common case pre-filled in.
+    pub fn count_ci(&self, z: f64) -> (u64, u64, u64) {
+        let estimate = self.count();
+        let rse = Self::expected_error_at(estimate);
+        let spread = estimate as f64 * z * rse;
+        let low = (estimate as f64 - spread).max(0.0) as u64;
+        let high = (estimate as f64 + spread) as u64;
+        (low, estimate, high)
+    }
+
+    #[must_use]
+    /// [`Self::count_ci`] at `z = 1.96`, the standard multiplier for a ~95% confidence
+    /// interval under the normal approximation.
+    pub fn count_ci95(&self) -> (u64, u64, u64) {
+        self.count_ci(1.96)
+    }
+
+    /// [`Self::assert_monotonic`]'s safety margin over [`Self::expected_error_at`]'s
+    /// *steady-state* relative error.
+    ///
+    /// `expected_error_at` models the estimator's asymptotic noise level, but the
+    /// single largest legitimate drop in `count()` happens right at a rescale
+    /// boundary, where `insert`'s near-saturation `active_count` makes the log-based
+    /// estimate transiently far more sensitive than that steady-state figure implies.
+    /// Empirically, worst-case single-rescale drops land at roughly 7-8x
+    /// `expected_error_at`'s prediction across sketch sizes from `M64` to `M4096`;
+    /// `12` keeps a margin above that without being so loose it stops catching real
+    /// bugs (e.g. a stray [`Self::clear_keep_scale`] or a corrupted merge, which drop
+    /// `count()` to near zero).
+    const RESCALE_JITTER_MULTIPLIER: f64 = 12.0;
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )]
+    /// Panics if [`Self::count`] has dropped from `prev` by more than the expected
+    /// rescale jitter allows, see [`Self::RESCALE_JITTER_MULTIPLIER`].
+    ///
+    /// The true cardinality only ever grows as more distinct elements are inserted, but
+    /// [`Self::merge_detailed`]'s ladder rescale rounds the estimate to a coarser
+    /// resolution each time it fires, which can nudge `count()` down noticeably even
+    /// though nothing regressed. This distinguishes that expected jitter from a real
+    /// bug by checking the drop against a generous multiple of
+    /// [`Self::expected_error_at`]'s modeled relative error at `prev`.
+    /// A validation/monitoring utility, not part of the hot insert path.
+    /// # Panics
+    /// If `count()` dropped below `prev` by more than the modeled noise budget.
+    pub fn assert_monotonic(&self, prev: u64) {
+        let current = self.count();
+        if current >= prev {
+            return;
         }
+        let drop = prev - current;
+        let allowed_drop =
+            (prev as f64 * Self::expected_error_at(prev) * Self::RESCALE_JITTER_MULTIPLIER) as u64;
+        assert!(
+            drop <= allowed_drop,
+            "count() dropped from {prev} to {current} (drop of {drop}), exceeding the \
+             expected rescale jitter budget of {allowed_drop}"
+        );
+    }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+    #[cfg(feature = "validation")]
+    #[allow(clippy::cast_precision_loss)]
+    /// Builds a sketch of this type from `data`, compares its estimate against the exact
+    /// distinct count computed via a `HashSet`, and returns the observed relative error.
+    ///
+    /// Returns `Ok(error)` if `error <= max_relative_error`, `Err(error)` otherwise. This
+    /// is gated behind the `validation` feature since it pays for an exact `HashSet` of
+    /// `data`, which is exactly the cost this sketch exists to avoid in production.
+    /// # Errors
+    /// Returns `Err(error)` with the observed relative error if it exceeds `max_relative_error`.
+    pub fn validate_accuracy<V: std::hash::Hash + Eq>(
+        data: &[V],
+        max_relative_error: f64,
+    ) -> Result<f64, f64> {
+        let mut sketch = Self::new();
+        for v in data {
+            sketch.insert(v);
         }
+        let exact: std::collections::HashSet<&V> = data.iter().collect();
+        let exact_count = exact.len() as f64;
+        let error = (sketch.count() as f64 - exact_count).abs() / exact_count;
+        if error <= max_relative_error {
+            Ok(error)
+        } else {
+            Err(error)
+        }
+    }
 
-        let hash = self.hash.hash_one(v3);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+    /// Estimates the set-difference cardinality `|self \ baseline|`, e.g. "new elements
+    /// since yesterday", as `union_count(self, baseline) - baseline.count()`, saturating
+    /// at zero.
+    ///
+    /// This is noisy when the true difference is small relative to `baseline`, since it
+    /// is derived from the difference of two independently-erroring estimates rather
+    /// than measured directly.
+    /// # Panics
+    /// If `self` and `baseline` were built with differently-seeded hashers, see [`Self::merge`].
+    pub fn difference_count(&self, baseline: &Self) -> u64
+    where
+        Self: Clone,
+    {
+        let mut union = self.clone();
+        union.merge(baseline.clone());
+        union.count().saturating_sub(baseline.count())
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    /// Returns the 25th/50th/75th percentile substream value (`0..=3`), computed from
+    /// the value histogram. Useful for diagnosing whether the sketch is under- or
+    /// over-saturated relative to `t`: a median stuck at `0` suggests `t` is too high
+    /// for the data seen so far, while a median at `3` suggests a rescale is overdue.
+    pub fn rank_quantiles(&self) -> (u8, u8, u8) {
+        let mut histogram = [0u32; 4];
+        for stream in 0..BITS::STREAMS {
+            histogram[self.sketch.val(stream) as usize] += 1;
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+        let quantile = |percentile: u32| {
+            let target = u64::from(BITS::STREAMS) * u64::from(percentile) / 100;
+            let mut cumulative = 0u64;
+            for (value, &count) in histogram.iter().enumerate() {
+                cumulative += u64::from(count);
+                if cumulative > target {
+                    return value as u8;
+                }
+            }
+            3
+        };
+        (quantile(25), quantile(50), quantile(75))
+    }
+
+    /// Number of substreams rendered per row by [`Self::to_ascii_heatmap`]. `STREAMS`
+    /// is always a multiple of this on every `BITS` this crate ships, so every row is
+    /// full.
+    const HEATMAP_COLUMNS: u32 = 8;
+
+    #[must_use]
+    /// Renders every substream's current value as a character in a fixed
+    /// [`Self::HEATMAP_COLUMNS`]-wide ASCII grid, one row per group of substreams: `.`
+    /// for an untouched substream (value `0`), or its decimal value otherwise (`1`..=`3`
+    /// for `h2b`'s two-bit substreams). Rows are newline-separated with no trailing
+    /// newline. For teaching and debugging: eyeball whether the sketch is filling
+    /// uniformly (a healthy hash) or shows visible bands or clusters (a hasher or
+    /// seeding bug).
+    pub fn to_ascii_heatmap(&self) -> String {
+        let rows = BITS::STREAMS / Self::HEATMAP_COLUMNS;
+        let mut out = String::with_capacity((BITS::STREAMS + rows) as usize);
+        for stream in 0..BITS::STREAMS {
+            let value = self.sketch.val(stream);
+            out.push(if value == 0 {
+                '.'
+            } else {
+                char::from(b'0' + value)
+            });
+            if (stream + 1) % Self::HEATMAP_COLUMNS == 0 && stream + 1 < BITS::STREAMS {
+                out.push('\n');
+            }
         }
+        out
+    }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+    /// Format version with no checksum, written by [`Self::to_bytes_without_checksum`].
+    const FORMAT_VERSION: u8 = 1;
+    /// Format version with an appended CRC-32, written by [`Self::to_bytes`].
+    const FORMAT_VERSION_CHECKSUMMED: u8 = 2;
+
+    #[must_use]
+    /// Serializes the sketch the same way as [`Self::to_bytes_without_checksum`], then
+    /// appends a little-endian `u32` CRC-32 of those bytes. Sketches stored in object
+    /// stores or shipped over the wire can get truncated or bit-flipped, and
+    /// [`Self::from_bytes`] verifies the checksum so a silent wrong cardinality doesn't
+    /// slip through as a decode error instead. Costs 4 extra bytes over
+    /// [`Self::to_bytes_without_checksum`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = self.to_bytes_without_checksum();
+        buf[0] = Self::FORMAT_VERSION_CHECKSUMMED;
+        let crc = crc32(&buf);
+        buf.extend_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    #[must_use]
+    /// Serializes the sketch into a versioned byte buffer, without a checksum: a
+    /// version byte, `t` as a little-endian `u32`, followed by one byte per substream
+    /// holding its value. The hasher itself is not serialized, so the same
+    /// `HASH`/`BITS` types must be used to interpret the result. Prefer
+    /// [`Self::to_bytes`] unless the 4 checksum bytes matter for your storage budget.
+    pub fn to_bytes_without_checksum(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(5 + BITS::STREAMS as usize);
+        buf.push(Self::FORMAT_VERSION);
+        buf.extend_from_slice(&self.t.to_le_bytes());
+        for stream in 0..BITS::STREAMS {
+            buf.push(self.sketch.val(stream));
         }
+        buf
+    }
 
-        let hash = self.hash.hash_one(v4);
-        // use most significant bits for k the rest for x
-        let stream: u32 = (hash >> BITS::IDX_SHIFT) as u32;
-        let hash: u64 = hash & BITS::HASH_MASK;
+    #[must_use]
+    /// Serializes just the plane bytes: one byte per substream holding its value, with
+    /// no version tag, checksum, or `t`. Cheaper than [`Self::to_bytes_without_checksum`]
+    /// per record, for an append-only WAL of sketch snapshots where the caller already
+    /// tracks `t` (and any format version) alongside each entry. Pair with
+    /// [`Self::load_sketch_bytes`] to reconstruct the sketch from a snapshot plus that
+    /// caller-tracked `t`.
+    pub fn sketch_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BITS::STREAMS as usize);
+        for stream in 0..BITS::STREAMS {
+            buf.push(self.sketch.val(stream));
+        }
+        buf
+    }
 
-        if hash.trailing_ones() >= self.t && self.sketch.val(stream) < 1 {
-            self.count += 1;
-            self.sketch.set(stream, 1);
+    /// Overwrites the inner sketch from bytes previously written by
+    /// [`Self::sketch_bytes`], then recomputes `count` to match. `t` is left untouched,
+    /// since [`Self::sketch_bytes`] doesn't carry it — the caller restores it separately
+    /// (e.g. from the same WAL record).
+    ///
+    /// # Panics
+    /// Panics if `bytes.len() != BITS::STREAMS as usize`.
+    pub fn load_sketch_bytes(&mut self, bytes: &[u8]) {
+        assert_eq!(
+            bytes.len(),
+            BITS::STREAMS as usize,
+            "sketch byte length mismatch"
+        );
+        for (stream, &value) in bytes.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            self.sketch.set(stream as u32, value);
         }
-        // 2^4
-        if hash.trailing_ones() >= self.t + 4 && self.sketch.val(stream) < 2 {
-            self.sketch.set(stream, 2);
+        self.recompute();
+    }
+
+    /// Fixed-width record size [`Self::pack_into`]/[`Self::unpack_from`] use per
+    /// sketch: `t` and `count` (4 bytes each) plus one byte per substream. Constant
+    /// across every sketch of this `BITS` type, so callers can compute the byte offset
+    /// of record `i` as `i * Self::PACKED_RECORD_LEN` without scanning.
+    pub const PACKED_RECORD_LEN: usize = 8 + BITS::STREAMS as usize;
+
+    /// Appends this sketch's packed record to `buf`: `t` and `count` as little-endian
+    /// `u32`s, followed by one byte per substream holding its value. Unlike
+    /// [`Self::to_bytes`], there's no version byte or checksum, and every record is
+    /// exactly [`Self::PACKED_RECORD_LEN`] bytes -- systems storing millions of
+    /// per-group sketches can pack them one after another into a single buffer or file
+    /// and seek straight to record `i` via [`Self::unpack_from`], rather than paying
+    /// for per-record framing or scanning to find a boundary.
+    ///
+    /// The hasher itself isn't packed, matching [`Self::to_bytes_without_checksum`]:
+    /// the same `HASH`/`BITS` types must be used to unpack.
+    pub fn pack_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.t.to_le_bytes());
+        buf.extend_from_slice(&self.active_count().to_le_bytes());
+        for stream in 0..BITS::STREAMS {
+            buf.push(self.sketch.val(stream));
         }
+    }
 
-        // 2^8
-        if hash.trailing_ones() >= self.t + 8 && self.sketch.val(stream) < 3 {
-            self.sketch.set(stream, 3);
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    /// Reads the packed record at `index` out of `buf`, as written by
+    /// [`Self::pack_into`]. Builder configuration like [`Self::with_max_scale`]'s cap
+    /// or [`Self::with_sampling`]'s rate isn't part of the packed record, so the
+    /// result comes back with the defaults [`Self::new`] would use for those.
+    ///
+    /// `buf`'s `count` field is trusted as-is rather than re-derived from the packed
+    /// register bytes, so a record from an untrusted source could claim a `count`
+    /// inconsistent with its own sketch bits. Check [`Self::is_consistent`] afterward
+    /// before trusting the result if `buf` didn't come from [`Self::pack_into`].
+    /// # Panics
+    /// Panics if `buf` doesn't hold a full record at `index`, i.e. if
+    /// `buf.len() < (index + 1) * Self::PACKED_RECORD_LEN`.
+    pub fn unpack_from(buf: &[u8], index: usize) -> Self {
+        let start = index * Self::PACKED_RECORD_LEN;
+        let record = &buf[start..start + Self::PACKED_RECORD_LEN];
+        let t = u32::from_le_bytes(record[0..4].try_into().expect("checked length above"));
+        let count = u32::from_le_bytes(record[4..8].try_into().expect("checked length above"));
+        #[cfg(feature = "compact")]
+        let _ = count;
+        let mut sketch = BITS::default();
+        for (stream, &value) in record[8..].iter().enumerate() {
+            sketch.set(stream as u32, value);
         }
+        Self {
+            hash: HASH::default(),
+            sketch,
+            #[cfg(not(feature = "compact"))]
+            count,
+            t,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        }
+    }
 
-        if self.count >= threshold {
-            self.count = self.sketch.decrement();
-            self.t += 4;
+    /// Deserializes a sketch previously written by [`Self::to_bytes`] or
+    /// [`Self::to_bytes_without_checksum`].
+    ///
+    /// The first byte is a format version that is dispatched to a decoder for that
+    /// specific historical format, so bytes written by older crate versions keep
+    /// decoding correctly as the format evolves.
+    /// # Errors
+    /// Returns [`DecodeError::UnsupportedVersion`] if the version byte is not
+    /// recognized, [`DecodeError::Truncated`] if `bytes` is too short for the version
+    /// it claims to be, [`DecodeError::ChecksumMismatch`] if the checksummed format's
+    /// CRC-32 doesn't match its payload, or [`DecodeError::InvalidRegisterValue`] if a
+    /// substream byte is outside the range `BITS::set` accepts.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes.first() {
+            Some(&Self::FORMAT_VERSION) => Self::decode_v1(&bytes[1..]),
+            Some(&Self::FORMAT_VERSION_CHECKSUMMED) => Self::decode_v2(bytes),
+            Some(&other) => Err(DecodeError::UnsupportedVersion(other)),
+            None => Err(DecodeError::Truncated),
         }
     }
 
-    /// returns the estimated count. This function is non destructive
-    /// and can be called multiple times without changing the state of the counter
-    #[inline]
+    /// Builds a sketch from an already-populated `sketch`/`t`/`hash`, computing `count`
+    /// from the sketch itself. Used by [`crate::h3b::HyperThreeBits::into_two_bits`] to
+    /// hand over a converted sketch without exposing the private fields it needs to set.
+    pub(crate) fn from_converted(hash: HASH, sketch: BITS, t: u32) -> Self {
+        #[cfg(not(feature = "compact"))]
+        let count = sketch.count();
+        Self {
+            hash,
+            sketch,
+            #[cfg(not(feature = "compact"))]
+            count,
+            t,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        }
+    }
+
+    #[cfg(any(test, feature = "raw"))]
+    #[must_use]
+    /// Builds a sketch directly from a `sketch`/`t`/`count` triple, bypassing
+    /// `insert`/`set` entirely. Intended for tests that need to start from a precise,
+    /// hand-built state (e.g. exercising `merge`) without driving it there via many
+    /// inserts. Gated behind the `raw` feature (or automatically available under
+    /// `#[cfg(test)]`) since bypassing the invariant that `count == sketch.count()` is
+    /// easy to get wrong outside test code.
+    pub fn from_sketch(sketch: BITS, t: u32, count: u32) -> Self {
+        #[cfg(feature = "compact")]
+        let _ = count;
+        Self {
+            hash: HASH::default(),
+            sketch,
+            #[cfg(not(feature = "compact"))]
+            count,
+            t,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        }
+    }
+
+    #[cfg(any(test, feature = "raw"))]
+    #[must_use]
+    /// Returns a reference to the inner [`Sketch`], for advanced tooling (histograms,
+    /// custom serializers) that needs to inspect register values this type doesn't
+    /// otherwise expose. Gated behind the `raw` feature (or automatically available
+    /// under `#[cfg(test)]`) since it's an escape hatch around the normal accessor
+    /// surface.
+    pub fn sketch(&self) -> &BITS {
+        &self.sketch
+    }
+
+    #[cfg(any(test, feature = "raw"))]
+    #[must_use]
+    /// Returns a mutable reference to the inner [`Sketch`], for tooling that needs to
+    /// set registers directly (e.g. replaying a foreign sketch's state via
+    /// [`Sketch::set`]). Mutating it directly can desynchronize the cached `count` from
+    /// the sketch's actual register contents, which throws off [`Self::count`]'s
+    /// estimate — call [`Self::recompute`] afterwards to bring `count` back in sync.
+    /// Gated behind the `raw` feature for the same reason as [`Self::sketch`].
+    pub fn sketch_mut(&mut self) -> &mut BITS {
+        &mut self.sketch
+    }
+
+    /// Refreshes the cached `count` from the sketch's actual register contents.
+    /// Necessary after mutating the sketch out-of-band, e.g. via
+    /// [`Self::sketch_mut`], since inserts normally keep `count` in sync incrementally
+    /// and a direct register write bypasses that bookkeeping. A safety valve for
+    /// advanced users who poke registers directly: it lets [`Self::count`] produce a
+    /// correct estimate again without having to rebuild the whole sketch from scratch.
+    #[allow(clippy::unused_self)]
+    pub fn recompute(&mut self) {
+        #[cfg(not(feature = "compact"))]
+        {
+            self.count = self.sketch.count();
+        }
+    }
+
+    #[must_use]
+    /// Checks that this sketch's cached active-substream count agrees with its actual
+    /// sketch bits, and that it's within bounds for `BITS::STREAMS`. Always `true` under
+    /// the `compact` feature, since there's no separate cached field to drift from
+    /// there. Useful after building a [`HyperTwoBits`] from untrusted or hand-built
+    /// state -- [`Self::unpack_from`] trusts a `count` it reads straight off the wire,
+    /// and [`Self::from_sketch`] takes one as a bare parameter -- rather than after
+    /// [`Self::from_bytes`], which always derives `count` from the decoded sketch bits
+    /// and so can't disagree with them in the first place.
+    pub fn is_consistent(&self) -> bool {
+        self.active_count() == self.sketch.count() && self.active_count() <= BITS::STREAMS
+    }
+
+    /// Verifies the trailing CRC-32 before delegating to [`Self::decode_v1`] for the
+    /// rest of the payload.
+    fn decode_v2(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let split = bytes.len().saturating_sub(4);
+        let (payload, crc_bytes) = bytes
+            .split_at_checked(split)
+            .ok_or(DecodeError::Truncated)?;
+        if crc_bytes.len() != 4 || payload.is_empty() {
+            return Err(DecodeError::Truncated);
+        }
+        let expected = u32::from_le_bytes(crc_bytes.try_into().expect("checked length above"));
+        if crc32(payload) != expected {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+        Self::decode_v1(&payload[1..])
+    }
+
+    fn decode_v1(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (t_bytes, values) = bytes.split_at_checked(4).ok_or(DecodeError::Truncated)?;
+        if values.len() != BITS::STREAMS as usize {
+            return Err(DecodeError::Truncated);
+        }
+        let t = u32::from_le_bytes(t_bytes.try_into().expect("checked length above"));
+        let mut sketch = BITS::default();
+        for (stream, &value) in values.iter().enumerate() {
+            if value > BITS::MAX_VALUE {
+                return Err(DecodeError::InvalidRegisterValue(value));
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            sketch.set(stream as u32, value);
+        }
+        #[cfg(not(feature = "compact"))]
+        let count = sketch.count();
+        Ok(Self {
+            hash: HASH::default(),
+            sketch,
+            #[cfg(not(feature = "compact"))]
+            count,
+            t,
+            merge_depth: 0,
+            sample_log2: 0,
+            split_index: false,
+            max_t: None,
+            at_capacity: false,
+            #[cfg(feature = "metrics")]
+            rescales: 0,
+            #[cfg(feature = "metrics")]
+            discards: 0,
+            #[cfg(feature = "track-inserts")]
+            total_inserts: 0,
+            #[cfg(feature = "minhash")]
+            minhash: [u64::MAX; MINHASH_K],
+            #[cfg(feature = "history")]
+            history: [0; HISTORY_CAPACITY],
+            #[cfg(feature = "history")]
+            history_next: 0,
+            #[cfg(feature = "history")]
+            history_full: false,
+        })
+    }
+
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    /// Snapshots plain, dependency-free metrics about this sketch's current state, for
+    /// downstream code to export to whatever observability system it uses (Prometheus
+    /// or otherwise) without having to re-derive them from private fields.
+    pub fn metrics(&self) -> SketchMetrics {
+        SketchMetrics {
+            estimate: self.count(),
+            fill_ratio: f64::from(self.active_count()) / f64::from(BITS::STREAMS),
+            scale_t: self.t,
+            rescales: self.rescales,
+            discards: self.discards,
+        }
+    }
+}
+
+/// Orders sketches by their estimated cardinality ([`HyperTwoBits::count`]), so that
+/// e.g. a `Vec<HyperTwoBits<_>>` can be sorted by size. Two sketches with equal
+/// estimates are considered equal for ordering purposes, even if their internal state
+/// (seed, exact substream values) differs.
+impl<
+        SKETCH: Sketch + Eq,
+        HASH: BuildHasher + Default + Eq,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > PartialOrd
+    for HyperTwoBits<SKETCH, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<
+        SKETCH: Sketch + Eq,
+        HASH: BuildHasher + Default + Eq,
+        const RESCALE_STEP: u32,
+        const DECREMENT_STEPS: u32,
+        const MERGE_FULL_PER_MILLE: u32,
+    > Ord for HyperTwoBits<SKETCH, HASH, RESCALE_STEP, DECREMENT_STEPS, MERGE_FULL_PER_MILLE>
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.count().cmp(&other.count())
+    }
+}
+
+/// Errors returned by [`HyperTwoBits::from_bytes`] when decoding a serialized sketch.
+#[derive(Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The byte slice was too short to hold the claimed format version.
+    Truncated,
+    /// The version byte did not match any format this crate version knows how to decode.
+    UnsupportedVersion(u8),
+    /// The checksummed format's CRC-32 did not match its payload, meaning the bytes
+    /// were corrupted or truncated in a way [`DecodeError::Truncated`] can't catch.
+    ChecksumMismatch,
+    /// A substream byte was outside `0..BITS::MAX_VALUE`, which [`Sketch::set`] requires
+    /// -- passing it through would trip that debug assertion in a debug build, or
+    /// silently mask to a different in-range value in release.
+    InvalidRegisterValue(u8),
+}
+
+/// Errors returned by [`HyperTwoBits::try_insert`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum InsertError {
+    /// The sketch has hit its [`HyperTwoBits::with_max_scale`]/[`HyperTwoBits::fixed_scale`]
+    /// cap (all substreams saturated at that scale), see [`HyperTwoBits::at_capacity`].
+    /// No further elements can change the estimate until the sketch is rebuilt at a
+    /// higher cap.
+    AtCapacity,
+}
+
+/// Minimal CRC-32 (IEEE 802.3, the same polynomial `zlib`/`gzip` use) implementation,
+/// so [`HyperTwoBits::to_bytes`] doesn't need a dependency for the one checksum this
+/// crate needs. `pub(crate)` since [`crate::h3b::HyperThreeBits::to_bytes`] reuses it
+/// for the same purpose.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = 0u32.wrapping_sub(crc & 1);
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Scores an observed/expected frequency histogram (as produced by
+/// [`HyperTwoBits::hasher_quality_sample`]) via a Pearson chi-square goodness-of-fit
+/// statistic, folded into `0.0..=1.0` where `1.0` is a perfect fit and the score decays
+/// smoothly as the statistic grows relative to the total expected count.
+fn chi_square_fit_score(buckets: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let (chi_square, expected_total) = buckets.fold(
+        (0.0, 0.0),
+        |(chi_square, expected_total), (observed, expected)| {
+            let diff = observed - expected;
+            (
+                chi_square + diff * diff / expected,
+                expected_total + expected,
+            )
+        },
+    );
+    expected_total / (expected_total + chi_square)
+}
+
+/// Outcome of a [`HyperTwoBits::merge_detailed`] call, recording which of the three
+/// merge cases was taken.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum MergeOutcome {
+    /// `self` and `other` had the same `t`, so their sketches were `or`ed together.
+    Merged,
+    /// `self` and `other` had a `t` difference of 1..=8, so `other`'s high bits were
+    /// merged into `self`'s low bits, see [`sketch::Sketch::merge_high_into_lo`].
+    MergedHighIntoLo,
+    /// `self` and `other`'s `t` differed by more than 8, so `other` was discarded
+    /// entirely and `self` was left unchanged.
+    Discarded,
+}
+
+/// Point-in-time snapshot of a [`HyperTwoBits`] counter's internal state, returned by
+/// [`HyperTwoBits::metrics`]. Plain data, dependency-free, so downstream code can export
+/// it to whatever metrics system it uses (e.g. as Prometheus gauges) without pulling in
+/// a metrics crate here.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SketchMetrics {
+    /// The estimated cardinality, i.e. [`HyperTwoBits::count`].
+    pub estimate: u64,
+    /// Fraction of substreams currently active (non-zero), in `0.0..=1.0`.
+    pub fill_ratio: f64,
+    /// Current value of the internal scale factor `t`.
+    pub scale_t: u32,
+    /// Number of rescales performed so far.
+    pub rescales: u32,
+    /// Number of merges discarded outright because `t` differed too much to bridge,
+    /// see [`HyperTwoBits::discard_count`].
+    pub discards: u32,
+}
+
+/// The core cardinality-estimation formula every estimator in this module builds on:
+/// `2^t * m * ln(1 / beta)`, where `beta = 1 - active / m`. Pulled out as a free, pure
+/// function (no sketch instance required) so it's unit-testable in isolation and so
+/// researchers can plug in their own `active`/`t`/`m` to explore the estimator's
+/// behavior -- e.g. the claim that its relative error stays roughly constant across
+/// cardinalities -- without building a sketch at all. [`HyperTwoBits::count`] (via
+/// `count_from`) and [`HyperTwoBits::count_f64`] both call this for the log-based
+/// regime, as does [`DefaultEstimator`].
+///
+/// `beta` reaches exactly `0` when `active == m` (every substream active), which would
+/// otherwise send `ln(1 / beta)` to infinity; this floors `beta` at [`f64::EPSILON`] so
+/// the result stays finite (if a very large overestimate) instead of saturating to
+/// `u64::MAX` once cast. [`HyperTwoBits::count_adaptive`] applies its own, tighter
+/// floor starting well before this point, for a genuinely accurate near-saturation
+/// estimate rather than merely a finite one.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn estimate_cardinality(active: u32, t: u32, m: u32) -> f64 {
+    let beta = (1.0 - f64::from(active) / f64::from(m)).max(f64::EPSILON);
+    #[cfg(feature = "fast-math")]
+    let bias = fast_ln(1.0 / beta);
+    #[cfg(not(feature = "fast-math"))]
+    let bias = (1.0 / beta).ln();
+
+    f64::from(t).exp2() * f64::from(m) * bias
+}
+
+/// A pluggable cardinality estimator, see [`HyperTwoBits::count_with`].
+///
+/// Implementations compute an estimate from the substream-activation `count`, the
+/// current scale factor `t`, and the substream total `m`, so the estimation formula
+/// itself becomes swappable without adding a `count_*` method per variant to
+/// [`HyperTwoBits`].
+pub trait Estimator {
+    /// Computes the cardinality estimate.
+    fn estimate(count: u32, t: u32, m: u32) -> u64;
+}
+
+/// Reproduces [`HyperTwoBits::count`]'s built-in formula, i.e. `2^t * m * ln(1 / beta)`
+/// where `beta = 1 - count / m`.
+#[derive(Debug, Clone, Copy)]
+pub struct DefaultEstimator;
+
+impl Estimator for DefaultEstimator {
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    pub fn count(&self) -> u64 {
-        let beta = 1.0 - f64::from(self.count) / f64::from(BITS::STREAMS);
-        let bias: f64 = (1.0 / beta).ln();
-        (f64::from(self.t).exp2() * f64::from(BITS::STREAMS) * bias) as u64
+    fn estimate(count: u32, t: u32, m: u32) -> u64 {
+        estimate_cardinality(count, t, m) as u64
+    }
+}
+
+/// Fast approximate `x.ln()` for `x >= 1`, used by [`HyperTwoBits::count`] in place of
+/// `f64::ln` when the `fast-math` feature is enabled.
+///
+/// Splits `x = m * 2^e` via `x`'s IEEE-754 bit layout (`m` in `[1, 2)`), then
+/// approximates `ln(m)` with the fast-converging series `ln(m) = 2 * atanh(y) = 2 * (y +
+/// y^3/3 + y^5/5 + y^7/7 + ...)` where `y = (m - 1) / (m + 1)`. `y` never exceeds `1/3`
+/// over `m`'s range, so four terms keep the relative error under `1e-5` — comfortably
+/// inside the `0.1%` this crate documents as acceptable for `count()`.
+#[cfg(feature = "fast-math")]
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+fn fast_ln(x: f64) -> f64 {
+    debug_assert!(x >= 1.0, "fast_ln is only calibrated for x >= 1");
+    let bits = x.to_bits();
+    // `(bits >> 52) & 0x7ff` is at most 0x7ff (2047), well within `i64` range.
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mantissa_bits = (bits & 0x000f_ffff_ffff_ffff) | (1023u64 << 52);
+    let m = f64::from_bits(mantissa_bits);
+
+    let y = (m - 1.0) / (m + 1.0);
+    let y2 = y * y;
+    let series = y * (1.0 + y2 * (1.0 / 3.0 + y2 * (1.0 / 5.0 + y2 * (1.0 / 7.0))));
+
+    exponent as f64 * std::f64::consts::LN_2 + 2.0 * series
+}
+
+/// Bits each h2b substream occupies in a sketch's storage (`0..=3`, one of four ladder
+/// values). Used by [`m_for_bytes`] to translate a byte budget into a substream count.
+pub const BITS_PER_STREAM: u32 = 2;
+
+/// Returns the largest substream count this crate ships a pre-built sketch for (`64`
+/// through `8192`, see [`M64`]..[`M8192`]) whose sketch storage -- `m * BITS_PER_STREAM`
+/// bits -- fits within `max_bytes`, or `0` if even [`M64`] doesn't fit.
+///
+/// Complements [`HyperTwoBits::expected_error_at`]: that picks `M` for a target
+/// accuracy, this picks `M` for a target memory footprint, e.g. to match an existing
+/// `HyperLogLog` deployment's byte budget for an equal-memory benchmark.
+///
+/// This only accounts for the sketch's own storage, not the fixed per-instance overhead
+/// `HyperTwoBits` carries alongside it (the hasher, `count`/`t`/`merge_depth`,
+/// bookkeeping flags, ...). Under the default hasher and no `metrics`/`track-inserts`
+/// features, that overhead is pinned by this module's own `size_of` checks below at 24
+/// bytes for [`M64`] and [`M128`] (word-based sketches) or 32 bytes for [`M256`] and up
+/// (register-array-based sketches). Budget for it separately if `max_bytes` needs to
+/// bound the whole counter rather than just the sketch.
+#[must_use]
+pub const fn m_for_bytes(max_bytes: usize) -> usize {
+    const SUPPORTED_M: [usize; 8] = [64, 128, 256, 512, 1024, 2048, 4096, 8192];
+    let mut best = 0;
+    let mut i = 0;
+    while i < SUPPORTED_M.len() {
+        let m = SUPPORTED_M[i];
+        let sketch_bytes = theoretical_bits(m) / 8;
+        if sketch_bytes <= max_bytes {
+            best = m;
+        }
+        i += 1;
     }
+    best
 }
+
+/// Returns the number of bits an `M`-substream sketch occupies in storage (`m *
+/// BITS_PER_STREAM`), the theoretical memory cost the Janson/Lumbroso/Sedgewick-style
+/// accuracy tables key off of. Use this (alongside [`crate::h3b::theoretical_bits`] and
+/// an equivalent bit count for `HyperLogLog`'s registers) to pick `M` values that put
+/// competing sketches on equal memory footing before comparing accuracy or throughput.
+#[must_use]
+pub const fn theoretical_bits(m: usize) -> usize {
+    m * BITS_PER_STREAM as usize
+}
+
+// `HyperTwoBits` has no interior mutability, so it's `Send`/`Sync` for any `Send + Sync`
+// `SKETCH`/`HASH`; `M4096` and the default hasher are just a fixed instantiation the
+// compiler can check. Safe to share sketches across threads or hold in async tasks.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HyperTwoBits<M4096>>();
+};
+
+// Pins the stack size of each sketch size so a layout regression (e.g. an added field)
+// fails the build instead of surfacing as a surprise at runtime. `metrics` (`rescales` +
+// `discards`, two `u32`s) and `track-inserts` (`total_inserts`, one `u64`) each add 8
+// bytes of state; either alone fits into existing padding for every size but `M64`, so
+// only `M64` grows when exactly one is enabled. With both enabled the two additions
+// don't share padding, so every size grows by a further 8 bytes on top of that.
+// `minhash` (`[u64; MINHASH_K]`, 512 bytes) is large enough to never share padding with
+// anything else, so every size simply grows by a further 512 bytes when it's enabled.
+// `history` (`[u32; HISTORY_CAPACITY]` plus a `usize` cursor and a `bool` flag, 137
+// bytes) grows every size by 128 bytes on its own, or 136 for `M64` -- but unlike
+// `minhash`, that increment isn't fixed once `metrics`/`track-inserts` are also
+// enabled, since how much of `history`'s 137 bytes lands in existing padding depends
+// on the total field layout; see the dedicated `history`-combination blocks below for
+// the actual measured sizes in those cases.
+#[cfg(not(any(
+    feature = "metrics",
+    feature = "track-inserts",
+    feature = "minhash",
+    feature = "history"
+)))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 40);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 64);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 96);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 160);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 288);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 544);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1056);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2080);
+};
+
+#[cfg(all(
+    not(feature = "minhash"),
+    not(feature = "history"),
+    any(
+        all(feature = "metrics", not(feature = "track-inserts")),
+        all(feature = "track-inserts", not(feature = "metrics"))
+    )
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 48);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 64);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 96);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 160);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 288);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 544);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1056);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2080);
+};
+
+#[cfg(all(
+    not(feature = "minhash"),
+    not(feature = "history"),
+    feature = "metrics",
+    feature = "track-inserts"
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 56);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 80);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 112);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 176);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 304);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 560);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1072);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2096);
+};
+
+#[cfg(all(
+    feature = "minhash",
+    not(feature = "history"),
+    not(any(feature = "metrics", feature = "track-inserts"))
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 40 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 64 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 96 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 160 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 288 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 544 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1056 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2080 + 512);
+};
+
+#[cfg(all(
+    feature = "minhash",
+    not(feature = "history"),
+    any(
+        all(feature = "metrics", not(feature = "track-inserts")),
+        all(feature = "track-inserts", not(feature = "metrics"))
+    )
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 48 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 64 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 96 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 160 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 288 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 544 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1056 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2080 + 512);
+};
+
+#[cfg(all(
+    feature = "history",
+    not(any(feature = "metrics", feature = "track-inserts", feature = "minhash"))
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 40 + 136);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 64 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 96 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 160 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 288 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 544 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1056 + 128);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2080 + 128);
+};
+
+// Unlike `metrics`/`track-inserts`/`minhash` above, `history`'s extra state (137 bytes:
+// a `[u32; HISTORY_CAPACITY]` array plus a `usize` cursor and a `bool` flag) doesn't
+// grow every size by the same fixed amount once combined with those other features --
+// the exact padding it displaces depends on the total field layout, not just its own
+// size -- so the blocks below spell out the measured sizes directly rather than adding
+// an assumed constant to the non-`history` blocks above.
+#[cfg(all(
+    feature = "history",
+    not(feature = "minhash"),
+    any(
+        all(feature = "metrics", not(feature = "track-inserts")),
+        all(feature = "track-inserts", not(feature = "metrics"))
+    )
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 184);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 208);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 240);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 304);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 432);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 688);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1200);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2224);
+};
+
+#[cfg(all(
+    feature = "history",
+    not(feature = "minhash"),
+    feature = "metrics",
+    feature = "track-inserts"
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 192);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 208);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 240);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 304);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 432);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 688);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1200);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2224);
+};
+
+#[cfg(all(
+    feature = "history",
+    feature = "minhash",
+    not(any(feature = "metrics", feature = "track-inserts"))
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 688);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 704);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 736);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 800);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 928);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 1184);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1696);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2720);
+};
+
+#[cfg(all(
+    feature = "history",
+    feature = "minhash",
+    any(
+        all(feature = "metrics", not(feature = "track-inserts")),
+        all(feature = "track-inserts", not(feature = "metrics"))
+    )
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 696);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 720);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 752);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 816);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 944);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 1200);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1712);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2736);
+};
+
+#[cfg(all(
+    feature = "history",
+    feature = "minhash",
+    feature = "metrics",
+    feature = "track-inserts"
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 704);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 720);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 752);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 816);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 944);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 1200);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1712);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2736);
+};
+
+#[cfg(all(
+    feature = "minhash",
+    not(feature = "history"),
+    feature = "metrics",
+    feature = "track-inserts"
+))]
+const _: () = {
+    assert!(std::mem::size_of::<HyperTwoBits<M64>>() == 56 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M128>>() == 80 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M256>>() == 112 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M512>>() == 176 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M1024>>() == 304 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M2048>>() == 560 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M4096>>() == 1072 + 512);
+    assert!(std::mem::size_of::<HyperTwoBits<M8192>>() == 2096 + 512);
+};
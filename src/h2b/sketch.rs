@@ -3,6 +3,12 @@
 /// this allows us let the compilare avoid know exaclty what M is
 /// and avoid conditionals, loops and branches.
 pub trait Sketch: Default {
+    /// Largest value a single substream can hold, i.e. the top of the ladder
+    /// `insert`/`decrement` climb and drop along. `3` here since h2b substreams are
+    /// 2 bits wide; `crate::h3b::Sketch::MAX_VALUE` is `7` for its 3-bit substreams.
+    /// Lets generic code (estimators, histograms, conversions) work across both
+    /// without hardcoding either width.
+    const MAX_VALUE: u8 = 3;
     /// Number of substreams
     const STREAMS: u32;
     /// bitmask for x, the most significant bits n bits are used so that 2^n = M
@@ -25,6 +31,30 @@ pub trait Sketch: Default {
     /// - self.lo = self.lo | other.hi
     /// - self.hi remains unchanged
     fn merge_high_into_lo(&mut self, other: &Self);
+
+    /// Builds a sketch directly from a per-substream value array, so tests can start
+    /// from a precise, hand-written state (e.g. `[0, 1, 2, 3, 0, ...]`) instead of
+    /// driving many `insert`/`set` calls to reach it. Gated the same way as
+    /// [`crate::h2b::HyperTwoBits::from_sketch`]: available under `#[cfg(test)]` or the
+    /// `raw` feature.
+    /// # Panics
+    /// Panics if `values.len() != Self::STREAMS as usize`, or if any value is `>= 4`
+    /// (via [`Self::set`]'s own debug assertion).
+    #[cfg(any(test, feature = "raw"))]
+    #[must_use]
+    fn from_values(values: &[u8]) -> Self {
+        assert_eq!(
+            values.len(),
+            Self::STREAMS as usize,
+            "expected exactly STREAMS values"
+        );
+        let mut sketch = Self::default();
+        for (stream, &value) in values.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            sketch.set(stream as u32, value);
+        }
+        sketch
+    }
 }
 
 /// M = 64, using two 64 bit integers to store the sketch
@@ -137,7 +167,7 @@ impl Sketch for M128 {
     }
     #[inline]
     fn decrement(&mut self) -> u32 {
-        let count = self.high.count_ones();
+        let count = crate::register::popcount128(self.high);
         self.low = self.high & !self.low;
         self.high &= !self.low;
         count
@@ -145,7 +175,7 @@ impl Sketch for M128 {
     #[inline]
     fn count(&self) -> u32 {
         let used_streams = self.high | self.low;
-        used_streams.count_ones()
+        crate::register::popcount128(used_streams)
     }
     #[inline]
     fn merge(&mut self, other: &Self) {
@@ -158,111 +188,99 @@ impl Sketch for M128 {
     }
 }
 
-/// We use a register to store hi and low bits together
-/// to optimize for cache locallity when compiting inside
-/// a vectored sketch
-#[derive(Default, Clone, Copy, Debug, Eq, PartialEq, Hash)]
-#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
-struct HiLoRegister {
-    high: u128,
-    low: u128,
-}
-/// Generic scatch using `REGISTERS` 128 bit `HiLoRegister`
+/// Generic vectored sketch built out of shared two-plane [`crate::register::BitRegister`]s,
 /// so the total M for the sketch is `REGISTERS` * 128.
 ///
 /// This is not meant to be used directly instead it serves as
 /// a base for the other vectored sketches
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
 #[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
 pub struct M128Reg<const REGISTERS: usize> {
-    registers: [HiLoRegister; REGISTERS],
-}
-
-impl<const REGISTERS: usize> Default for M128Reg<REGISTERS> {
-    fn default() -> Self {
-        Self {
-            registers: [HiLoRegister { high: 0, low: 0 }; REGISTERS],
-        }
-    }
+    inner: crate::register::M128Reg<REGISTERS, 2>,
 }
 
 impl<const REGISTERS: usize> M128Reg<REGISTERS> {
-    const REG_SIZE: usize = 128;
     #[inline]
     fn val(&self, stream: u32) -> u8 {
-        // Calculate the index in the sketch vector
-        let register_index = stream as usize / Self::REG_SIZE;
-        // calculate the left over index into the sketc
-        let bit_index = stream as usize % Self::REG_SIZE;
-        // Calculate the high bit
-        let high_bit = ((self.registers[register_index].high >> bit_index) & 1) as u8;
-        // Calculate the low bit
-        let low_bit = ((self.registers[register_index].low >> bit_index) & 1) as u8;
-        (high_bit << 1) | low_bit
+        self.inner.val(stream)
     }
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(value < 4);
-        // Calculate the index in the sketch vector
-        let register_index = stream as usize / Self::REG_SIZE;
-        // calculate the left over index into the sketc
-        let bit_index = stream as usize % Self::REG_SIZE;
-
-        // split value in it's respective bits
-        let value = u128::from(value);
-        let value_high_bit = (value >> 1) & 1;
-        let value_low_bit = value & 1;
+        self.inner.set(stream, value);
+    }
+    #[inline]
+    fn decrement(&mut self) -> u32 {
+        self.inner.decrement()
+    }
+    #[inline]
+    fn count(&self) -> u32 {
+        self.inner.count()
+    }
+    #[inline]
+    fn merge(&mut self, other: &Self) {
+        #[cfg(feature = "simd")]
+        self.inner.merge_simd(&other.inner);
+        #[cfg(not(feature = "simd"))]
+        self.inner.merge(&other.inner);
+    }
+    #[inline]
+    fn merge_high_into_lo(&mut self, other: &Self) {
+        self.inner.merge_high_into_lo(&other.inner);
+    }
+}
 
-        // reset all bits at index
-        self.registers[register_index].high &= !(1 << bit_index);
-        self.registers[register_index].low &= !(1 << bit_index);
+/// Generic vectored sketch built out of shared two-plane
+/// [`crate::register::Register64`]s, the `u64` counterpart to [`M128Reg`] so the total M
+/// for the sketch is `REGISTERS * 64`.
+///
+/// `u128` arithmetic is emulated in software on 32-bit targets, so [`M256`]..[`M8192`]
+/// alias to this instead of [`M128Reg`] there, keeping every register op on a native
+/// machine word. Bit-for-bit equivalent to [`M128Reg`] at the same `M`: both store one
+/// bit of a substream's value per plane, just packed into differently-sized words.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[cfg(any(target_pointer_width = "32", test))]
+pub struct M64Reg<const REGISTERS: usize> {
+    inner: crate::register::M64Reg<REGISTERS, 2>,
+}
 
-        // set the bits at index to the value
-        self.registers[register_index].high |= value_high_bit << bit_index;
-        self.registers[register_index].low |= value_low_bit << bit_index;
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const REGISTERS: usize> M64Reg<REGISTERS> {
+    #[inline]
+    fn val(&self, stream: u32) -> u8 {
+        self.inner.val(stream)
+    }
+    #[inline]
+    fn set(&mut self, stream: u32, value: u8) {
+        debug_assert!(value < 4);
+        self.inner.set(stream, value);
     }
     #[inline]
     fn decrement(&mut self) -> u32 {
-        let mut count = 0;
-        // Decrement by decrementing each register
-        for register in &mut self.registers {
-            count += register.high.count_ones();
-            register.low = register.high & !register.low;
-            register.high &= !register.low;
-        }
-        count
+        self.inner.decrement()
     }
     #[inline]
     fn count(&self) -> u32 {
-        let mut count = 0;
-        // Count the number of active substreams by counting them for each register
-        // and summing them up
-        for registers in self.registers {
-            count += (registers.high | registers.low).count_ones();
-        }
-        count
+        self.inner.count()
     }
     #[inline]
     fn merge(&mut self, other: &Self) {
-        // Merge by merging each register
-        for (self_register, other_register) in self.registers.iter_mut().zip(other.registers.iter())
-        {
-            self_register.high |= other_register.high;
-            self_register.low |= other_register.low;
-        }
+        self.inner.merge(&other.inner);
     }
     #[inline]
     fn merge_high_into_lo(&mut self, other: &Self) {
-        // Merge by merging each register
-        for (self_register, other_register) in self.registers.iter_mut().zip(other.registers.iter())
-        {
-            self_register.low |= other_register.high;
-        }
+        self.inner.merge_high_into_lo(&other.inner);
     }
 }
 
 /// M = 256 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M256 = M128Reg<2>;
+/// M = 256 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M256 = M64Reg<4>;
 
 impl Sketch for M256 {
     const STREAMS: u32 = 256;
@@ -301,7 +319,12 @@ impl Sketch for M256 {
 }
 
 /// M = 512 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M512 = M128Reg<4>;
+/// M = 512 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M512 = M64Reg<8>;
 
 impl Sketch for M512 {
     const STREAMS: u32 = 512;
@@ -340,7 +363,12 @@ impl Sketch for M512 {
 }
 
 /// M = 1024 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M1024 = M128Reg<8>;
+/// M = 1024 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M1024 = M64Reg<16>;
 
 impl Sketch for M1024 {
     const STREAMS: u32 = 1024;
@@ -379,7 +407,12 @@ impl Sketch for M1024 {
 }
 
 /// M = 2048 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M2048 = M128Reg<16>;
+/// M = 2048 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M2048 = M64Reg<32>;
 
 impl Sketch for M2048 {
     const STREAMS: u32 = 2048;
@@ -418,7 +451,12 @@ impl Sketch for M2048 {
 }
 
 /// M = 4096 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M4096 = M128Reg<32>;
+/// M = 4096 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M4096 = M64Reg<64>;
 
 impl Sketch for M4096 {
     const STREAMS: u32 = 4096;
@@ -456,14 +494,19 @@ impl Sketch for M4096 {
     }
 }
 
-/// M = 4096 Sketch Implementation
+/// M = 8192 Sketch Implementation
+#[cfg(not(target_pointer_width = "32"))]
 pub type M8192 = M128Reg<64>;
+/// M = 8192 Sketch Implementation, `Register64`-backed for 32-bit targets, see
+/// [`M64Reg`].
+#[cfg(target_pointer_width = "32")]
+pub type M8192 = M64Reg<128>;
 
 impl Sketch for M8192 {
-    const STREAMS: u32 = 4096;
+    const STREAMS: u32 = 8192;
     const HASH_MASK: u64 =
-        0b0000_0000_0000_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111;
-    const IDX_SHIFT: u32 = 52;
+        0b0000_0000_0000_0111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111;
+    const IDX_SHIFT: u32 = 51;
 
     #[inline]
     fn val(&self, stream: u32) -> u8 {
@@ -534,6 +577,49 @@ mod tests {
         }
     }
 
+    // `M64Reg` (the `Register64`-backed counterpart to `M128Reg`) only backs `M256`..
+    // `M8192` on 32-bit targets, so it can't be reached through the `test::<S: Sketch>`
+    // helper above on this host. Exercise its own `val`/`set`/`count`/`merge`/
+    // `merge_high_into_lo`/`decrement` directly instead, mirroring what `test` checks
+    // for the `Sketch`-implementing aliases.
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_m64reg<const REGISTERS: usize>() {
+        let streams = (REGISTERS * 64) as u32;
+        let mut s = M64Reg::<REGISTERS>::default();
+        for i in 0..streams {
+            assert_eq!(s.val(i), 0);
+            for r in 1..=3 {
+                s.set(i, r);
+                assert_eq!(s.val(i), r);
+            }
+            s.set(i, 0);
+        }
+
+        let mut lo = M64Reg::<REGISTERS>::default();
+        let mut hi = M64Reg::<REGISTERS>::default();
+        for i in 0..streams {
+            hi.set(i, 3);
+        }
+        lo.merge(&hi);
+        assert_eq!(lo.count(), streams);
+
+        let mut lo_target = M64Reg::<REGISTERS>::default();
+        lo_target.merge_high_into_lo(&hi);
+        for i in 0..streams {
+            assert_eq!(lo_target.val(i), 1);
+        }
+
+        assert_eq!(hi.decrement(), streams);
+        for i in 0..streams {
+            assert_eq!(hi.val(i), 2);
+        }
+    }
+
+    #[test]
+    fn test_m64reg_two_registers() {
+        test_m64reg::<2>();
+    }
+
     #[test]
     fn test_m64() {
         test::<M64>();
@@ -562,4 +648,8 @@ mod tests {
     fn test_m4096() {
         test::<M4096>();
     }
+    #[test]
+    fn test_m8192() {
+        test::<M8192>();
+    }
 }
@@ -1,37 +1,17 @@
-use super::{HyperTwoBits, Sketch, M4096};
+use std::hash::BuildHasher;
+
+use super::{
+    estimate_cardinality, DecodeError, DefaultEstimator, HyperTwoBits, MergeOutcome, Sketch, M1024,
+    M256, M4096, M512, M64, M8192,
+};
+use crate::AHasherDefaultBuilder;
 
 use std::io::{BufRead, BufReader};
 
 use hyperloglog::HyperLogLog;
 
-#[test]
-fn htb64_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M64>>(), 24);
-}
-#[test]
-fn htb128_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M128>>(), 48);
-}
-#[test]
-fn htb256_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M256>>(), 80);
-}
-#[test]
-fn htb512_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M512>>(), 144);
-}
-#[test]
-fn htb1024_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M1024>>(), 272);
-}
-#[test]
-fn htb2048_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M2048>>(), 528);
-}
-#[test]
-fn htb4096_size() {
-    assert_eq!(std::mem::size_of::<HyperTwoBits<super::M4096>>(), 1040);
-}
+// Layout sizes are pinned via compile-time `const` assertions in `h2b.rs` itself now,
+// so a regression fails to compile rather than surfacing here at test time.
 
 #[allow(
     clippy::cast_precision_loss,
@@ -92,6 +72,123 @@ fn run<S: Sketch>(f: &str, actual: usize, delta: f64, mut n: usize) -> std::io::
     Ok(())
 }
 
+#[test]
+fn test_expected_error_at_matches_shakespeare() -> std::io::Result<()> {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let buf = BufReader::new(std::fs::File::open("data/Shakespeare.csv")?);
+    for line in buf.lines() {
+        htb.insert(&line?.as_bytes());
+    }
+    let actual = 35594.0;
+    #[allow(clippy::cast_precision_loss)]
+    let observed = (actual - htb.count() as f64).abs() / actual;
+    let expected = HyperTwoBits::<M4096>::expected_error_at(35_594);
+    assert!(
+        observed < expected * 10.0 + 0.02,
+        "observed error {observed} far exceeds modeled error {expected}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_jackknife_error_is_plausible_for_a_filled_sketch() {
+    let mut htb: HyperTwoBits<M512> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        htb.insert(&i);
+    }
+
+    let error = htb.jackknife_error();
+    #[allow(clippy::cast_precision_loss)]
+    let count = htb.count() as f64;
+    assert!(
+        error > 0.0 && error < count,
+        "jackknife error {error} implausible for a count of {count}"
+    );
+}
+
+#[test]
+fn test_count_ci95_brackets_the_true_count_on_ulysses_across_seeds() -> std::io::Result<()> {
+    use crate::SipHasher13Builder;
+
+    let text = std::fs::read_to_string("data/Ulysses.csv")?;
+    let actual = 35_343u64;
+
+    // Pin the hasher to a handful of fixed seeds rather than the process-random default
+    // builder, so this test is reproducible across `cargo test` invocations while still
+    // checking the interval holds up across more than one hashing of the corpus.
+    let mut brackets = 0;
+    let seeds = [1u64, 2, 3, 4, 5];
+    for &seed in &seeds {
+        let mut htb: HyperTwoBits<M4096, SipHasher13Builder> = HyperTwoBits::new();
+        htb.rotate_seed(seed);
+        for line in text.lines() {
+            htb.insert(&line.as_bytes());
+        }
+        let (low, estimate, high) = htb.count_ci95();
+        assert!(low <= estimate && estimate <= high, "interval out of order");
+        if low <= actual && actual <= high {
+            brackets += 1;
+        }
+    }
+
+    assert!(
+        brackets >= seeds.len() - 1,
+        "count_ci95 bracketed the true count in only {brackets}/{} seeds",
+        seeds.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_compact_matches_the_cached_count_field() {
+    use crate::SipHasher13Builder;
+
+    // Dropping the cached `count` field under the `compact` feature is only worth doing
+    // if it doesn't change what callers observe -- run the same fixed-seed insert
+    // sequence a non-`compact` build is expected to produce `4_856` for (verified by
+    // hand against the default build before writing this in), and check `compact`
+    // still lands on it via `Self::active_count`'s recomputation path.
+    let mut htb: HyperTwoBits<M4096, SipHasher13Builder> = HyperTwoBits::new();
+    htb.rotate_seed(7);
+    for i in 0..5_000u64 {
+        htb.insert(&i);
+    }
+    assert_eq!(htb.count(), 4_856);
+}
+
+#[test]
+fn test_merge_any_downsamples_larger_sketch_into_smaller_accumulator() {
+    let mut accumulator: HyperTwoBits<M1024> = HyperTwoBits::new();
+    for i in 0..2_000u64 {
+        accumulator.insert(&i);
+    }
+
+    let mut shard: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 2_000..10_000u64 {
+        shard.insert(&i);
+    }
+
+    accumulator.merge_any(&shard);
+
+    // 10_000 distinct elements folded into an M1024 accumulator: well within its
+    // asymptotic relative error, same as if all of them had been inserted directly.
+    #[allow(clippy::cast_precision_loss)]
+    let error = (accumulator.count() as f64 - 10_000.0).abs() / 10_000.0;
+    assert!(
+        error < HyperTwoBits::<M1024>::expected_error_at(10_000) * 10.0 + 0.05,
+        "merge_any produced an implausible estimate: {} (error {error})",
+        accumulator.count()
+    );
+}
+
+#[test]
+#[should_panic(expected = "divide")]
+fn test_merge_any_rejects_a_larger_accumulator() {
+    let mut accumulator: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let shard: HyperTwoBits<M1024> = HyperTwoBits::new();
+    accumulator.merge_any(&shard);
+}
+
 fn test_all(f: &str, actual: usize, delta: f64, n: usize) -> std::io::Result<()> {
     // we only test M4096 for now to sazve time when running tests
     // it's the medium tradeoff between space and precision, for HLL we use error rate of 0.00408
@@ -141,22 +238,1737 @@ fn test_ulysses_100_000() -> std::io::Result<()> {
 }
 
 #[test]
-fn test_war_and_peace() -> std::io::Result<()> {
-    test_all("data/War_and_Peace.csv", 22668, 0.1, usize::MAX)
+fn test_populate_from_hashes() {
+    let hashes: Vec<u64> = (0..10_000u64)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect();
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for &hash in &hashes {
+        htb.insert_hash(hash);
+    }
+
+    let mut htb_populated: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb_populated.populate_from_hashes(hashes);
+
+    assert_eq!(htb.count(), htb_populated.count());
 }
+
 #[test]
-fn test_war_and_peace_100() -> std::io::Result<()> {
-    test_all("data/War_and_Peace.csv", 70, 0.20, 100)
+fn test_from_hash_stream_builds_byte_identical_sketches() {
+    let hashes: Vec<u64> = (0..10_000u64)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect();
+
+    let a: HyperTwoBits<M4096> = HyperTwoBits::from_hash_stream(&hashes);
+    let b: HyperTwoBits<M4096> = HyperTwoBits::from_hash_stream(&hashes);
+
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(a.sketch.val(stream), b.sketch.val(stream));
+    }
+    assert_eq!(a.t, b.t);
+    assert_eq!(a.active_count(), b.active_count());
+    assert_eq!(a.count(), b.count());
 }
+
 #[test]
-fn test_war_and_peace_1_000() -> std::io::Result<()> {
-    test_all("data/War_and_Peace.csv", 200, 0.13, 1_000)
+fn test_insert_str_and_insert_bytes_agree_unlike_generic_insert() {
+    let s = "distinct-key";
+
+    let mut via_bytes: HyperTwoBits<M64> = HyperTwoBits::new();
+    via_bytes.insert_bytes(s.as_bytes());
+    let mut via_str: HyperTwoBits<M64> = HyperTwoBits::new();
+    via_str.insert_str(s);
+    for stream in 0..M64::STREAMS {
+        assert_eq!(via_bytes.sketch.val(stream), via_str.sketch.val(stream));
+    }
+
+    // `insert`'s generic `Hash for str`/`Hash for [u8]` paths hash the same logical
+    // string differently (a `0xff` sentinel vs a length prefix), so a naive `&str`
+    // insert and a `&[u8]` insert of the same key produce different hashes -- checked
+    // directly on the 64-bit hash rather than through the sketch, since the sketch's
+    // few-bit-per-stream quantization occasionally maps two distinct hashes to the
+    // same (stream, rank) pair by pure chance.
+    let via_generic_str = via_bytes.hash.hash_one(s);
+    let via_generic_bytes = via_bytes.hash.hash_one(s.as_bytes());
+    assert_ne!(
+        via_generic_str, via_generic_bytes,
+        "Hash for str and Hash for [u8] were expected to diverge for the same bytes"
+    );
 }
+
 #[test]
-fn test_war_and_peace_10_000() -> std::io::Result<()> {
-    test_all("data/War_and_Peace.csv", 2030, 0.1, 10_000)
+fn test_insert_with_two_hashers() {
+    use crate::{AHasherDefaultBuilder, SipHasher13DefaultBuilder};
+
+    let ahash = AHasherDefaultBuilder::default();
+    let siphash = SipHasher13DefaultBuilder::default();
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        if i % 2 == 0 {
+            htb.insert_with(&ahash, &i);
+        } else {
+            htb.insert_with(&siphash, &i);
+        }
+    }
+    assert!(htb.count() > 0);
 }
+
 #[test]
-fn test_war_and_peace_100_000() -> std::io::Result<()> {
-    test_all("data/War_and_Peace.csv", 8248, 0.1, 100_000)
+fn test_insert_n_matches_single_insert() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb.insert(&"x");
+
+    let mut htb_n: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb_n.insert_n(&"x", 1000);
+
+    // Compares cardinality-relevant state rather than full struct equality: under the
+    // `track-inserts` feature, `insert_n(&x, 1000)` legitimately advances
+    // `total_inserts` by 1000 while leaving the sketch itself unchanged from a single
+    // `insert`, since it counts elements processed rather than distinct elements.
+    assert_eq!(htb.count(), htb_n.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(htb.sketch.val(stream), htb_n.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_insert_composite_matches_insert_of_tuple() {
+    let mut composite: HyperTwoBits<M4096> = HyperTwoBits::new();
+    composite.insert_composite(&1u64, &2u64);
+
+    let mut tuple: HyperTwoBits<M4096> = HyperTwoBits::new();
+    tuple.insert(&(1u64, 2u64));
+
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(composite.sketch.val(stream), tuple.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_insert_composite_is_order_sensitive() {
+    // A single pair has a small but real chance of `(a, b)` and `(b, a)` landing on the
+    // same (stream, rank) by pure luck, so insert many pairs -- across that many
+    // inserts, the two sketches ending up bit-for-bit identical is vanishingly unlikely
+    // unless order genuinely doesn't matter.
+    let mut forward: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut backward: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        forward.insert_composite(&i, &(i + 1));
+        backward.insert_composite(&(i + 1), &i);
+    }
+
+    assert_ne!(forward, backward);
+}
+
+#[test]
+fn test_count_pins_small_cardinality_without_spurious_doubling() {
+    let empty: HyperTwoBits<M64> = HyperTwoBits::new();
+    assert_eq!(empty.count(), 0, "an empty sketch must estimate zero");
+
+    // Built directly via `from_sketch` rather than `insert`, since which specific
+    // element activates which substream depends on the (randomly-seeded, by default)
+    // hasher — this pins the substream count itself, not a particular input.
+    let mut sketch = M64::default();
+    sketch.set(0, 1);
+    let single: HyperTwoBits<M64> = HyperTwoBits::from_sketch(sketch, 1, 1);
+    assert_eq!(
+        single.count(),
+        1,
+        "a single active substream must not be doubled by the t == 1 scale factor"
+    );
+
+    // A handful of active substreams must be reported as exactly that many, not
+    // inflated by the log-based formula's `2^t` scale factor.
+    let mut sketch = M64::default();
+    for stream in 0..5 {
+        sketch.set(stream, 1);
+    }
+    let few: HyperTwoBits<M64> = HyperTwoBits::from_sketch(sketch, 1, 5);
+    assert_eq!(few.count(), 5);
+}
+
+#[test]
+fn test_two_step_decrement_still_produces_reasonable_estimates() {
+    // `DECREMENT_STEPS = 2` halves resolution twice per rescale instead of once, so `t`
+    // has to advance by `RESCALE_STEP * DECREMENT_STEPS = 8` to match.
+    let mut htb: HyperTwoBits<M4096, crate::AHasherDefaultBuilder, 4, 2> = HyperTwoBits::new();
+    for i in 0..200_000u64 {
+        htb.insert(&i);
+    }
+
+    let actual = 200_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {}",
+        htb.count()
+    );
+}
+
+#[test]
+fn test_insert_chunk_matches_element_wise_insert_with_remainder() {
+    // 4_003 is neither a multiple of 4 nor of 2, so this exercises the `insert4` path,
+    // the `insert2` path on what's left after that, and finally the single-element
+    // `insert` path on what's left after that.
+    let values: Vec<u64> = (0..4_003u64).collect();
+
+    let mut chunked: HyperTwoBits<M4096> = HyperTwoBits::new();
+    chunked.insert_chunk(&values);
+
+    let mut one_by_one: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for v in &values {
+        one_by_one.insert(v);
+    }
+
+    assert_eq!(chunked.count(), one_by_one.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(chunked.sketch.val(stream), one_by_one.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_insert_array_matches_element_wise_insert() {
+    let values: Vec<u64> = (0..8_000u64).collect();
+
+    let mut batched: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for chunk in values.chunks_exact(8) {
+        let array: &[u64; 8] = chunk.try_into().unwrap();
+        batched.insert_array(array);
+    }
+
+    let mut one_by_one: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for v in &values {
+        one_by_one.insert(v);
+    }
+
+    assert_eq!(batched.count(), one_by_one.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(batched.sketch.val(stream), one_by_one.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_count_merge_corrected_is_closer_to_truth_after_deep_merges() {
+    // Build a sketch whose registers already reflect more active substreams than we're
+    // pretending actually went into it, mimicking the overestimation bias that repeated
+    // merges near the `t` boundary accumulate.
+    let mut sketch = M4096::default();
+    for stream in 0..2_000 {
+        sketch.set(stream, 1);
+    }
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::from_sketch(sketch, 1, 2_000);
+    assert_eq!(htb.merge_depth(), 0);
+
+    // Merging a sketch into an identical copy of itself at the same `t` is a no-op on
+    // the registers (`OR`-ing identical bits changes nothing), which isolates
+    // `merge_depth`'s effect on the estimate from any actual change in cardinality.
+    for _ in 0..10 {
+        htb.merge(htb.clone());
+    }
+    assert_eq!(htb.merge_depth(), 10);
+
+    let actual = 1_800.0_f64;
+    let raw = htb.count();
+    let corrected = htb.count_merge_corrected(htb.merge_depth());
+
+    #[allow(clippy::cast_precision_loss)]
+    let raw_delta = (actual - raw as f64).abs();
+    #[allow(clippy::cast_precision_loss)]
+    let corrected_delta = (actual - corrected as f64).abs();
+    assert!(
+        corrected_delta < raw_delta,
+        "corrected estimate should be closer to the true cardinality: raw={raw}, corrected={corrected}, actual={actual}"
+    );
+}
+
+#[test]
+fn test_subset_fraction_of_a_known_half_cohort_is_close_to_half() {
+    let mut whole: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut cohort: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        whole.insert(&i);
+        if i % 2 == 0 {
+            cohort.insert(&i);
+        }
+    }
+
+    let fraction = cohort.subset_fraction(&whole);
+    assert!(
+        (fraction - 0.5).abs() < 0.1,
+        "expected roughly half, got {fraction}"
+    );
+}
+
+#[test]
+fn test_subset_fraction_of_an_empty_whole_is_zero() {
+    let cohort: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let whole: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert!(cohort.subset_fraction(&whole).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_split_index_region_matches_top_bits_accuracy_at_m8192() {
+    // M8192 needs 13 index bits, the most of any sketch size, so it's the sharpest case
+    // for comparing the default top-bits region against `with_split_index_region`.
+    let actual = 200_000u64;
+    let mut top_bits: HyperTwoBits<M8192> = HyperTwoBits::new();
+    let mut split_region: HyperTwoBits<M8192> = HyperTwoBits::new().with_split_index_region();
+    for i in 0..actual {
+        top_bits.insert(&i);
+        split_region.insert(&i);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let delta = |estimate: u64| (actual as f64 - estimate as f64).abs() / actual as f64;
+    let delta_top = delta(top_bits.count());
+    let delta_split = delta(split_region.count());
+    assert!(
+        delta_top < 0.15,
+        "top-bits indexing delta too high: {delta_top}, count: {}",
+        top_bits.count()
+    );
+    assert!(
+        delta_split < 0.15,
+        "split-region indexing delta too high: {delta_split}, count: {}",
+        split_region.count()
+    );
+}
+
+#[test]
+fn test_insert_array_honors_split_index_region() {
+    // insert_array (and therefore insert2/insert4/insert_chunk, which forward to it)
+    // must route through split_hash the same way insert/insert_hash do, or a counter
+    // built with with_split_index_region() would assign streams inconsistently
+    // depending on which insert path a caller happened to use.
+    let mut via_insert: HyperTwoBits<M8192> = HyperTwoBits::new().with_split_index_region();
+    let mut via_insert2: HyperTwoBits<M8192> = HyperTwoBits::new().with_split_index_region();
+    for i in 0..5_000u64 {
+        via_insert.insert(&i);
+        via_insert.insert(&(i + 1));
+        via_insert2.insert2(&i, &(i + 1));
+    }
+
+    for stream in 0..M8192::STREAMS {
+        assert_eq!(
+            via_insert.sketch().val(stream),
+            via_insert2.sketch().val(stream)
+        );
+    }
+}
+
+#[test]
+fn test_m_for_bytes_maps_byte_budgets_to_supported_m() {
+    // Below the smallest supported sketch (`M64`, 64 * 2 bits = 16 bytes): nothing fits.
+    assert_eq!(super::m_for_bytes(0), 0);
+    assert_eq!(super::m_for_bytes(15), 0);
+    // Exactly `M64`'s footprint.
+    assert_eq!(super::m_for_bytes(16), 64);
+    // Between `M256` (64 bytes) and `M512` (128 bytes): picks the largest that still fits.
+    assert_eq!(super::m_for_bytes(100), 256);
+    // Exactly `M8192`'s footprint, the largest supported sketch.
+    assert_eq!(super::m_for_bytes(2048), 8192);
+    // Comfortably above the largest supported sketch: still caps at `M8192`.
+    assert_eq!(super::m_for_bytes(1_000_000), 8192);
+}
+
+#[test]
+fn test_with_max_scale_caps_t_and_reports_capacity() {
+    // `t` starts at 1, so capping at 1 means the very first rescale a busy `M64`
+    // sketch would otherwise trigger has to be refused instead of advancing `t`.
+    let mut htb: HyperTwoBits<M64> = HyperTwoBits::new().with_max_scale(1);
+    assert!(!htb.at_capacity());
+
+    for i in 0..100_000u64 {
+        htb.insert(&i);
+    }
+
+    assert!(htb.at_capacity());
+    let (t, _, _) = htb.count_components();
+    assert_eq!(t, 1, "t must not advance past the max_scale cap");
+
+    // still produces an estimate rather than panicking; capped this early it's a heavy
+    // undercount of the true 100_000 cardinality.
+    let actual = 100_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64) / actual;
+    assert!(
+        delta > 0.5,
+        "expected a heavy undercount, got delta {delta}"
+    );
+}
+
+#[test]
+fn test_try_insert_reports_at_capacity_once_saturated() {
+    let mut htb: HyperTwoBits<M64> = HyperTwoBits::new().with_max_scale(1);
+
+    for i in 0..100_000u64 {
+        if htb.try_insert(&i) == Err(super::InsertError::AtCapacity) {
+            break;
+        }
+    }
+
+    assert!(htb.at_capacity());
+    assert_eq!(
+        htb.try_insert(&"anything"),
+        Err(super::InsertError::AtCapacity)
+    );
+}
+
+#[test]
+fn test_calibrate_reaches_a_higher_t_than_the_default() {
+    let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+    let sample: Vec<u64> = (0..10_000u64).collect();
+    htb.calibrate(&sample);
+
+    let (t, count, _) = htb.count_components();
+    assert!(t > 1, "expected calibration to advance t, got t = {t}");
+    assert_eq!(count, 0, "calibrate must not leave any sample data behind");
+}
+
+#[test]
+fn test_clear_keep_scale_zeroes_count_but_preserves_t() {
+    let mut htb: HyperTwoBits<M64> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let (t_before, _, _) = htb.count_components();
+    assert!(
+        t_before > 1,
+        "expected the busy M64 sketch to have rescaled at least once"
+    );
+
+    htb.clear_keep_scale();
+
+    let (t_after, count, _) = htb.count_components();
+    assert_eq!(t_after, t_before, "clear_keep_scale must not reset t");
+    assert_eq!(count, 0);
+    assert!(!htb.at_capacity());
+}
+
+#[test]
+fn test_rotate_seed_changes_hashing_and_clears_the_sketch() {
+    use crate::AHasherBuilder;
+
+    let mut htb: HyperTwoBits<M64, AHasherBuilder> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let hash_before = htb.hash.hash_one(42);
+    assert!(htb.count() > 0);
+
+    htb.rotate_seed(0xdead_beef);
+
+    assert_ne!(
+        htb.hash.hash_one(42),
+        hash_before,
+        "rotate_seed must replace the hash seed"
+    );
+    assert_eq!(htb.count(), 0, "rotate_seed must clear the sketch");
+    assert!(!htb.at_capacity());
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_validate_accuracy() {
+    let data: Vec<u32> = (0..5_000).chain(0..5_000).collect();
+    let error = HyperTwoBits::<M4096>::validate_accuracy(&data, 0.1).unwrap();
+    assert!(error <= 0.1);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics_reflect_state() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..200_000u64 {
+        htb.insert(&i);
+    }
+    let metrics = htb.metrics();
+    assert_eq!(metrics.estimate, htb.count());
+    assert!(metrics.fill_ratio > 0.0 && metrics.fill_ratio <= 1.0);
+    assert!(
+        metrics.scale_t > 1,
+        "t should have advanced past its initial value"
+    );
+    assert!(
+        metrics.rescales > 0,
+        "inserting 200k values should have rescaled"
+    );
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_discard_count_increments_on_far_apart_merge() {
+    // `a` keeps `t = 20` throughout: `merge_detailed` always swaps so `self` ends up
+    // the larger-`t` party, and a swap would carry `b`'s own (zeroed) `discards` into
+    // `a`'s slot instead of accumulating -- keeping `a.t` the larger of the two here
+    // avoids that, so successive discards actually accumulate on it.
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    a.t = 20;
+    assert_eq!(a.discard_count(), 0);
+
+    let b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert_eq!(a.merge_detailed(b), MergeOutcome::Discarded);
+    assert_eq!(a.discard_count(), 1);
+    assert_eq!(a.metrics().discards, 1);
+
+    let c: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert_eq!(a.merge_detailed(c), MergeOutcome::Discarded);
+    assert_eq!(a.discard_count(), 2);
+}
+
+#[cfg(feature = "track-inserts")]
+#[test]
+fn test_total_inserts_counts_every_insert_call() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    // Duplicates still count: `total_inserts` tracks elements processed, not distinct
+    // elements, unlike `count()`.
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    htb.insert2(&1_000u64, &1_001u64);
+    htb.insert4(&1_002u64, &1_003u64, &1_004u64, &1_005u64);
+    htb.insert_n(&1_006u64, 50);
+
+    assert_eq!(htb.total_inserts(), 2_000 + 2 + 4 + 50);
+}
+
+#[cfg(feature = "minhash")]
+#[test]
+fn test_minhash_jaccard_beats_inclusion_exclusion_on_a_small_overlap() {
+    use crate::SipHasher13Builder;
+
+    // `a` and `b` overlap in only 100 of their 10_100 combined distinct elements
+    // (true Jaccard = 100 / 20_000 = 0.005): a small-overlap case where
+    // inclusion-exclusion's `count() + count() - union.count()` subtracts two large,
+    // individually-noisy estimates to recover a tiny difference.
+    //
+    // Both sides are pinned to the same fixed seed via `rotate_seed` (called before any
+    // insert, so it only fixes the hasher rather than discarding real state), and use
+    // `SipHasher13Builder` rather than the `AHasher`-backed builders: `ahash`'s default
+    // keys are themselves randomized once per process, so even a fixed `reseed` value
+    // still hashed differently across separate `cargo test` invocations, making which
+    // elements land in a small bottom-k sample seed-sensitive in a way this test
+    // couldn't see or control. `SipHasher13`'s default keys are fixed, so reseeding it
+    // gives fully reproducible hashing across runs.
+    let mut a: HyperTwoBits<M4096, SipHasher13Builder> = HyperTwoBits::new();
+    a.rotate_seed(5);
+    for i in 0..10_000u64 {
+        a.insert(&i);
+    }
+    let mut b: HyperTwoBits<M4096, SipHasher13Builder> = HyperTwoBits::new();
+    b.rotate_seed(5);
+    for i in 9_900..20_000u64 {
+        b.insert(&i);
+    }
+    let true_jaccard = 100.0 / 20_000.0;
+
+    let minhash_jaccard = a.minhash_jaccard(&b);
+
+    let mut union = a.clone();
+    assert_eq!(union.merge_detailed(b.clone()), MergeOutcome::Merged);
+    #[allow(clippy::cast_precision_loss)]
+    let inclusion_exclusion_jaccard =
+        (a.count() + b.count()).saturating_sub(union.count()) as f64 / union.count().max(1) as f64;
+
+    let minhash_error = (minhash_jaccard - true_jaccard).abs();
+    let inclusion_exclusion_error = (inclusion_exclusion_jaccard - true_jaccard).abs();
+    assert!(
+        minhash_error < inclusion_exclusion_error,
+        "expected minhash_jaccard ({minhash_jaccard}, error {minhash_error}) to beat \
+         inclusion-exclusion ({inclusion_exclusion_jaccard}, error {inclusion_exclusion_error}) \
+         for a true Jaccard of {true_jaccard}"
+    );
+}
+
+#[test]
+fn test_to_from_bytes_roundtrip() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let bytes = htb.to_bytes();
+    let decoded = HyperTwoBits::<M4096>::from_bytes(&bytes).unwrap();
+    assert_eq!(htb.count(), decoded.count());
+}
+
+#[test]
+fn test_from_bytes_detects_flipped_byte() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let mut bytes = htb.to_bytes();
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0xFF;
+    assert_eq!(
+        HyperTwoBits::<M4096>::from_bytes(&bytes).unwrap_err(),
+        DecodeError::ChecksumMismatch
+    );
+}
+
+#[test]
+fn test_to_bytes_without_checksum_roundtrips() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let bytes = htb.to_bytes_without_checksum();
+    let decoded = HyperTwoBits::<M4096>::from_bytes(&bytes).unwrap();
+    assert_eq!(htb.count(), decoded.count());
+}
+
+#[test]
+fn test_from_bytes_rejects_out_of_range_register_value_unchecksummed() {
+    // Corrupts a substream byte to a value `set` can't accept, going through the
+    // *unchecksummed* v1 path directly -- the checksummed path's CRC would catch this
+    // corruption before it ever reached `decode_v1`, so it can't exercise this check.
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let mut bytes = htb.to_bytes_without_checksum();
+    // Byte layout: version (1) + `t` (4, little-endian) + one byte per substream.
+    bytes[5] = 0xFF;
+    assert_eq!(
+        HyperTwoBits::<M4096>::from_bytes(&bytes).unwrap_err(),
+        DecodeError::InvalidRegisterValue(0xFF)
+    );
+}
+
+#[test]
+fn test_to_bytes_is_little_endian() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let t = htb.t;
+
+    let bytes = htb.to_bytes_without_checksum();
+
+    // version byte, then `t` as a little-endian u32, then one byte per substream.
+    assert_eq!(bytes[0], 1);
+    assert_eq!(&bytes[1..5], &t.to_le_bytes());
+
+    // `t` is written little-endian regardless of host architecture: decoding it back
+    // with `from_le_bytes` on any target (including simulated big-endian ones, which
+    // we model here with `swap_bytes` since this sandbox has no BE target to build
+    // for) must reproduce the original value.
+    let word = u32::from_le_bytes(bytes[1..5].try_into().unwrap());
+    let round_tripped_on_be = u32::from_be_bytes(bytes[1..5].try_into().unwrap()).swap_bytes();
+    assert_eq!(word, round_tripped_on_be);
+}
+
+#[test]
+fn test_pack_into_is_little_endian() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let t = htb.t;
+    let active_count = htb.active_count();
+
+    let mut buf = Vec::new();
+    htb.pack_into(&mut buf);
+
+    // `t` and `active_count` are written as little-endian u32s, followed by one byte
+    // per substream.
+    assert_eq!(&buf[0..4], &t.to_le_bytes());
+    assert_eq!(&buf[4..8], &active_count.to_le_bytes());
+
+    for (chunk, expected) in [(&buf[0..4], t), (&buf[4..8], active_count)] {
+        let word = u32::from_le_bytes(chunk.try_into().unwrap());
+        let round_tripped_on_be = u32::from_be_bytes(chunk.try_into().unwrap()).swap_bytes();
+        assert_eq!(word, expected);
+        assert_eq!(word, round_tripped_on_be);
+    }
+}
+
+#[test]
+fn test_sketch_bytes_roundtrip_preserves_registers_and_count() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+    let bytes = htb.sketch_bytes();
+    assert_eq!(bytes.len(), M4096::STREAMS as usize);
+
+    let mut loaded: HyperTwoBits<M4096> = HyperTwoBits::new();
+    loaded.load_sketch_bytes(&bytes);
+
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(loaded.sketch().val(stream), htb.sketch().val(stream));
+    }
+    assert_eq!(loaded.count(), htb.count());
+}
+
+#[test]
+fn test_union_count_many_five_shards() {
+    let mut shards: Vec<HyperTwoBits<M4096>> = Vec::new();
+    for shard in 0..5u64 {
+        let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+        for i in 0..2_000u64 {
+            htb.insert(&(shard * 2_000 + i));
+        }
+        shards.push(htb);
+    }
+    let union_count = HyperTwoBits::<M4096>::union_count_many(&shards);
+    let actual = 10_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - union_count as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {union_count}"
+    );
+}
+
+#[test]
+fn test_combined_estimate_sums_disjoint_shards() {
+    // Each shard sees a disjoint range of the keyspace, so the sum of their individual
+    // estimates should approximate the known true total of 10_000.
+    let mut shards: Vec<HyperTwoBits<M4096>> = Vec::new();
+    for shard in 0..5u64 {
+        let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+        for i in 0..2_000u64 {
+            htb.insert(&(shard * 2_000 + i));
+        }
+        shards.push(htb);
+    }
+    let combined = HyperTwoBits::<M4096>::combined_estimate(&shards);
+    let actual = 10_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - combined as f64).abs() / actual;
+    assert!(delta < 0.15, "delta too high: {delta}, count: {combined}");
+}
+
+#[test]
+fn test_estimate_merge_error_grows_with_t_difference() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..50_000u64 {
+        a.insert(&i);
+    }
+
+    let mut same_t = a.clone();
+    for i in 50_000..60_000u64 {
+        same_t.insert(&i);
+    }
+
+    let mut diff_t: HyperTwoBits<M4096> = HyperTwoBits::with_capacity_hint(50_000_000);
+    for i in 0..10_000u64 {
+        diff_t.insert(&i);
+    }
+    assert_ne!(a.t, diff_t.t, "test setup requires differing t");
+
+    assert!(a.estimate_merge_error(&same_t) < a.estimate_merge_error(&diff_t));
+}
+
+#[test]
+fn test_maybe_contains() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        htb.insert(&i);
+    }
+    for i in 0..5_000u64 {
+        assert!(
+            htb.maybe_contains(&i),
+            "inserted element {i} should be reported as possibly seen"
+        );
+    }
+
+    // Unseen elements whose own rank is high are unlikely to collide with a stream
+    // that already reached that tier, so most should be reported absent. Low-rank
+    // unseen elements are excluded from this check since tier 0 is trivially
+    // satisfied by every stream, making them frequent, expected false positives.
+    let mut high_rank_unseen = 0usize;
+    let mut high_rank_false_positives = 0usize;
+    for i in 5_000..50_000u64 {
+        let hash = htb.hash.hash_one(i);
+        let rank = (hash & M4096::HASH_MASK).trailing_ones();
+        if rank >= htb.t + 8 {
+            high_rank_unseen += 1;
+            if htb.maybe_contains(&i) {
+                high_rank_false_positives += 1;
+            }
+        }
+    }
+    assert!(
+        high_rank_unseen > 0,
+        "test setup should produce some high-rank unseen elements"
+    );
+    assert!(
+        high_rank_false_positives < high_rank_unseen / 2,
+        "expected most high-rank unseen elements to be reported absent: {high_rank_false_positives}/{high_rank_unseen}"
+    );
+}
+
+#[test]
+fn test_influence_provably_raised_substream_is_certain() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        htb.insert(&i);
+    }
+
+    // An unseen element whose rank clears a tier the substream hasn't reached yet would
+    // provably raise it if inserted, so `influence` should report certainty rather than
+    // fall back to the rank-based heuristic.
+    let raising_element = (5_000..50_000u64)
+        .find(|&i| {
+            let hash = htb.hash.hash_one(i);
+            let (stream, rank_hash) = htb.split_hash(hash);
+            rank_hash.trailing_ones() >= htb.t + 8 && htb.sketch.val(stream) == 0
+        })
+        .expect("test setup should find an element that would raise its substream");
+
+    assert!((htb.influence(&raising_element) - 1.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_influence_prefers_higher_rank_elements() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        htb.insert(&i);
+    }
+
+    // Collect unseen elements that could not provably raise their substream (so
+    // `influence` falls back to its rank-based heuristic for all of them), then compare
+    // the lowest- and highest-rank candidates found.
+    let mut candidates: Vec<(u64, u32)> = Vec::new();
+    for i in 5_000..200_000u64 {
+        let hash = htb.hash.hash_one(i);
+        let (stream, rank_hash) = htb.split_hash(hash);
+        let rank = rank_hash.trailing_ones();
+        let expected_tier =
+            u8::from(rank >= htb.t) + u8::from(rank >= htb.t + 4) + u8::from(rank >= htb.t + 8);
+        if expected_tier <= htb.sketch.val(stream) {
+            candidates.push((i, rank));
+        }
+        if candidates.len() >= 200 {
+            break;
+        }
+    }
+    let &(low_rank, low) = candidates.iter().min_by_key(|&&(_, rank)| rank).unwrap();
+    let &(high_rank, high) = candidates.iter().max_by_key(|&&(_, rank)| rank).unwrap();
+    assert!(
+        high > low,
+        "test setup should find candidates with distinct ranks"
+    );
+
+    assert!(
+        htb.influence(&high_rank) > htb.influence(&low_rank),
+        "a higher-rank element should report more influence than a lower-rank one"
+    );
+}
+
+#[test]
+fn test_from_bytes_hand_crafted_v1() {
+    // A v1 blob for M64: version byte, t = 1 (LE u32), then STREAMS value bytes.
+    let mut bytes = vec![1u8];
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend(std::iter::repeat_n(0u8, super::M64::STREAMS as usize));
+    let decoded = HyperTwoBits::<super::M64>::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.count(), 0);
+}
+
+#[test]
+fn test_pack_into_and_unpack_from_survive_random_access() {
+    use rand::seq::SliceRandom as _;
+
+    let mut buf = Vec::new();
+    let mut originals: Vec<HyperTwoBits<M64>> = Vec::with_capacity(1_000);
+    for group in 0..1_000u64 {
+        let mut htb: HyperTwoBits<M64> = HyperTwoBits::new();
+        for i in 0..(group % 50) {
+            htb.insert(&(group * 1_000 + i));
+        }
+        htb.pack_into(&mut buf);
+        originals.push(htb);
+    }
+    assert_eq!(buf.len(), 1_000 * HyperTwoBits::<M64>::PACKED_RECORD_LEN);
+
+    let mut indices: Vec<usize> = (0..1_000).collect();
+    indices.shuffle(&mut rand::thread_rng());
+    for index in indices {
+        let restored: HyperTwoBits<M64> = HyperTwoBits::unpack_from(&buf, index);
+        assert_eq!(restored.count(), originals[index].count());
+        assert_eq!(restored.t, originals[index].t);
+        for stream in 0..M64::STREAMS {
+            assert_eq!(
+                restored.sketch.val(stream),
+                originals[index].sketch.val(stream)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_is_consistent_on_a_normally_built_sketch() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert!(htb.is_consistent());
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    assert!(htb.is_consistent());
+}
+
+#[cfg(not(feature = "compact"))]
+#[test]
+fn test_is_consistent_flags_a_tampered_packed_count() {
+    // Under `compact` there's no cached `count` field for `unpack_from` to trust in the
+    // first place -- it's always recomputed from the sketch bits -- so a forged count in
+    // `buf` can't desync anything there; this only applies without `compact`.
+    let mut htb: HyperTwoBits<M64> = HyperTwoBits::new();
+    for i in 0..20u64 {
+        htb.insert(&i);
+    }
+    let mut buf = Vec::new();
+    htb.pack_into(&mut buf);
+
+    // Forge the packed record's `count` field (bytes 4..8) to claim far more active
+    // substreams than the sketch bits that follow it actually have.
+    buf[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let tampered: HyperTwoBits<M64> = HyperTwoBits::unpack_from(&buf, 0);
+    assert!(
+        !tampered.is_consistent(),
+        "a forged count must be caught by is_consistent"
+    );
+}
+
+#[test]
+fn test_from_bytes_unsupported_version() {
+    let bytes = vec![99u8];
+    assert_eq!(
+        HyperTwoBits::<M4096>::from_bytes(&bytes),
+        Err(DecodeError::UnsupportedVersion(99))
+    );
+}
+
+#[test]
+fn test_difference_count() {
+    let mut yesterday: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        yesterday.insert(&i);
+    }
+    let mut today = yesterday.clone();
+    for i in 5_000..10_000u64 {
+        today.insert(&i);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let diff = today.difference_count(&yesterday) as f64;
+    assert!((diff - 5_000.0).abs() / 5_000.0 < 0.15);
+}
+
+#[test]
+fn test_insert_fixed_width_u64_keys() {
+    let keys: Vec<u64> = (0..10_000u64).collect();
+    let mut data = Vec::with_capacity(keys.len() * 8);
+    for key in &keys {
+        data.extend_from_slice(&key.to_le_bytes());
+    }
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb.insert_fixed_width(&data, 8);
+
+    let actual = 10_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.1,
+        "delta too high: {delta}, count: {}",
+        htb.count()
+    );
+}
+
+#[test]
+#[should_panic(expected = "width must be non-zero")]
+fn test_insert_fixed_width_rejects_zero_width() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb.insert_fixed_width(&[1, 2, 3], 0);
+}
+
+#[test]
+fn test_count_with_default_estimator_matches_count() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..50_000u64 {
+        htb.insert(&i);
+    }
+    assert_eq!(htb.count_with::<DefaultEstimator>(), htb.count());
+}
+
+#[test]
+fn test_count_adaptive_matches_count_at_low_fill() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5u64 {
+        htb.insert(&i);
+    }
+    assert_eq!(htb.count_adaptive(), htb.count());
+}
+
+#[test]
+fn test_count_adaptive_matches_count_at_mid_fill() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..50_000u64 {
+        htb.insert(&i);
+    }
+    assert_eq!(htb.count_adaptive(), htb.count());
+}
+
+#[test]
+fn test_count_adaptive_beats_count_near_saturation() {
+    // Build an M64 sketch that's been pinned at capacity right at the ALPHA (98.8%)
+    // rescale threshold, so `count()`'s `ln(1 / beta)` term is close enough to
+    // diverging that `count_adaptive`'s floor on `beta` should visibly rein it in.
+    let mut sketch = M64::default();
+    for stream in 0..64u32 {
+        sketch.set(stream, 1);
+    }
+    let htb: HyperTwoBits<M64> = HyperTwoBits::from_sketch(sketch, 1, 64);
+
+    assert!(
+        htb.count_adaptive() < htb.count(),
+        "expected the saturation floor to pull the estimate down: count={}, count_adaptive={}",
+        htb.count(),
+        htb.count_adaptive()
+    );
+}
+
+#[test]
+fn test_estimate_cardinality_matches_known_input_output_pairs() {
+    // 128 active out of 256 substreams, t == 0: beta = 0.5, ln(2) ~ 0.693147.
+    let estimate = estimate_cardinality(128, 0, 256);
+    assert!(
+        (estimate - 177.445_678_223_346).abs() < 1e-9,
+        "got {estimate}"
+    );
+
+    // Doubling `t` doubles the estimate, all else equal.
+    let doubled_t = estimate_cardinality(128, 1, 256);
+    assert!((doubled_t - estimate * 2.0).abs() < 1e-9, "got {doubled_t}");
+
+    // `active == 0` means `beta == 1`, `ln(1 / 1) == 0`, so the estimate is `0`.
+    assert!((estimate_cardinality(0, 3, 256) - 0.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_estimate_cardinality_guards_against_beta_reaching_zero() {
+    // `active == m` drives beta to exactly `0`; without a guard `ln(1 / beta)` is
+    // infinite and the formula blows up rather than returning a (very large but)
+    // finite overestimate.
+    let estimate = estimate_cardinality(64, 1, 64);
+    assert!(
+        estimate.is_finite() && estimate > 0.0,
+        "expected a finite, positive overestimate, got {estimate}"
+    );
+}
+
+#[test]
+fn test_count_f64_matches_count_for_typical_fill() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..50_000u64 {
+        htb.insert(&i);
+    }
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    let truncated = htb.count_f64() as u64;
+    assert_eq!(truncated, htb.count());
+}
+
+#[test]
+fn test_merge_from_slice() {
+    let mut shards: Vec<HyperTwoBits<M4096>> = Vec::new();
+    for shard in 0..4u64 {
+        let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+        for i in 0..2_500u64 {
+            htb.insert(&(shard * 2_500 + i));
+        }
+        shards.push(htb);
+    }
+
+    let mut merged = shards[0].clone();
+    merged.merge_from_slice(&shards[1..]);
+
+    let actual = 10_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - merged.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {}",
+        merged.count()
+    );
+}
+
+#[test]
+fn test_peek_merge_count_matches_actual_merge() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..20_000u64 {
+        a.insert(&i);
+    }
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 15_000..40_000u64 {
+        b.insert(&i);
+    }
+
+    let peeked = a.peek_merge_count(&b);
+
+    a.merge(b);
+    assert_eq!(peeked, a.count());
+}
+
+#[test]
+fn test_insert_ascii_ci() {
+    let mut upper: HyperTwoBits<M4096> = HyperTwoBits::new();
+    upper.insert_ascii_ci(b"FOO");
+    let mut lower: HyperTwoBits<M4096> = HyperTwoBits::new();
+    lower.insert_ascii_ci(b"foo");
+    assert_eq!(upper, lower);
+}
+
+#[test]
+fn test_decrement_threshold() {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let expected = (0.988 * f64::from(M4096::STREAMS)) as u32;
+    assert_eq!(HyperTwoBits::<M4096>::decrement_threshold(), expected);
+}
+
+#[test]
+fn test_will_rescale() {
+    let htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert!(!htb.will_rescale());
+
+    let threshold = HyperTwoBits::<M4096>::decrement_threshold();
+    let mut values = vec![0u8; M4096::STREAMS as usize];
+    for value in values.iter_mut().take(threshold as usize - 1) {
+        *value = 1;
+    }
+    let sketch = M4096::from_values(&values);
+    let near_threshold = HyperTwoBits::<M4096>::from_sketch(sketch, 1, threshold - 1);
+    assert!(near_threshold.will_rescale());
+}
+
+#[test]
+fn test_assert_monotonic_does_not_panic_on_a_monotone_insert_stream() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut prev = 0;
+    for i in 0..200_000u64 {
+        htb.insert(&i);
+        let count = htb.count();
+        htb.assert_monotonic(prev);
+        prev = count;
+    }
+}
+
+#[test]
+#[should_panic(expected = "exceeding the expected rescale jitter budget")]
+fn test_assert_monotonic_panics_on_a_large_drop() {
+    let htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb.assert_monotonic(htb.count() + 1_000_000);
+}
+
+#[test]
+fn test_ord_by_estimated_cardinality() {
+    let mut small: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..100u64 {
+        small.insert(&i);
+    }
+    let mut medium: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..5_000u64 {
+        medium.insert(&i);
+    }
+    let mut large: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..50_000u64 {
+        large.insert(&i);
+    }
+
+    let mut sketches = [large, small, medium];
+    sketches.sort();
+
+    let counts: Vec<u64> = sketches.iter().map(HyperTwoBits::count).collect();
+    assert!(counts[0] < counts[1] && counts[1] < counts[2]);
+}
+
+#[test]
+fn test_different_rescale_step_converges() -> std::io::Result<()> {
+    // base-4 scaling (RESCALE_STEP = 2) instead of the default base-16 (RESCALE_STEP = 4)
+    let mut htb: HyperTwoBits<M4096, crate::AHasherDefaultBuilder, 2> = HyperTwoBits::new();
+    let buf = BufReader::new(std::fs::File::open("data/Ulysses.csv")?);
+    for line in buf.lines() {
+        htb.insert(&line?.as_bytes());
+    }
+    let actual = 35343.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {}",
+        htb.count()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_larger_rescale_step_also_converges_with_a_different_error_profile() -> std::io::Result<()> {
+    // RESCALE_STEP = 8 reproduces base-256 scaling: a coarser ladder that advances `t`
+    // in bigger jumps than the default base-16 (RESCALE_STEP = 4), rescaling less often
+    // at the cost of resolution between tiers. It's the opposite direction from
+    // `test_different_rescale_step_converges`'s base-4 (finer, more frequent rescales) --
+    // both converge, but not to the same error, since the ladder spacing itself changes
+    // how much information a rescale throws away.
+    //
+    // `RESCALE_STEP` only reshapes the ladder logic; it doesn't touch the sketch's
+    // storage, so `size_of::<HyperTwoBits<M4096, _, 8>>()` is identical to the default
+    // -- the tradeoff this const generic exposes is accuracy, not memory.
+    let mut htb: HyperTwoBits<M4096, crate::AHasherDefaultBuilder, 8> = HyperTwoBits::new();
+    let buf = BufReader::new(std::fs::File::open("data/Ulysses.csv")?);
+    for line in buf.lines() {
+        htb.insert(&line?.as_bytes());
+    }
+    let actual = 35343.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {}",
+        htb.count()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_detailed_merged() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert_eq!(a.merge_detailed(b), MergeOutcome::Merged);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "merge discarded"))]
+fn test_merge_flags_far_apart_discard_in_debug_builds() {
+    // `merge` (unlike `merge_detailed`) throws away the outcome, so a far-apart `t`
+    // that gets silently discarded is exactly the kind of surprise this debug-only
+    // invariant exists to catch during development; release builds keep the old
+    // silent-discard behavior.
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    b.t = 10;
+    a.merge(b);
+}
+
+#[test]
+fn test_merge_detailed_merged_high_into_lo() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    b.t = 5;
+    assert_eq!(a.merge_detailed(b), MergeOutcome::MergedHighIntoLo);
+}
+
+#[test]
+fn test_merge_detailed_discarded() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    b.t = 10;
+    assert_eq!(a.merge_detailed(b), MergeOutcome::Discarded);
+}
+
+#[test]
+fn test_merge_full_fraction_default_matches_alpha_sibling_constant() {
+    assert!((HyperTwoBits::<M4096>::merge_full_fraction() - 0.99).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_merge_full_fraction_affects_pre_merge_decrement() {
+    type Loose = HyperTwoBits<M4096, AHasherDefaultBuilder, 4, 1, 990>;
+    type Strict = HyperTwoBits<M4096, AHasherDefaultBuilder, 4, 1, 500>;
+
+    // Fill half the substreams -- above a `500`-per-mille (50%) merge-full threshold,
+    // but well below the default `990`-per-mille (99%) one -- so the two type-level
+    // configurations disagree on whether `self` is "nearly full" and should rescale
+    // before `other` is folded in.
+    #[allow(clippy::cast_possible_truncation)]
+    let half_full = M4096::STREAMS / 2;
+    let mut values = vec![0u8; M4096::STREAMS as usize];
+    for value in values.iter_mut().take(half_full as usize) {
+        *value = 1;
+    }
+    let t = 20;
+
+    let sketch = M4096::from_values(&values);
+    let mut loose = Loose::from_sketch(sketch, t, half_full);
+    let other = Loose::from_sketch(M4096::default(), t, 0);
+    loose.merge_detailed(other);
+    assert_eq!(
+        loose.t, t,
+        "990-per-mille threshold shouldn't have fired yet"
+    );
+
+    let sketch = M4096::from_values(&values);
+    let mut strict = Strict::from_sketch(sketch, t, half_full);
+    let other = Strict::from_sketch(M4096::default(), t, 0);
+    strict.merge_detailed(other);
+    assert_eq!(
+        strict.t,
+        t + 4,
+        "500-per-mille threshold should have fired a rescale"
+    );
+}
+
+#[test]
+fn test_merge_rescaled_is_accurate_at_the_maximum_allowed_t_gap() {
+    let mut small: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..2_000u64 {
+        small.insert(&i);
+    }
+
+    // Insert enough distinct values that `large`'s own capacity-triggered rescale
+    // fires twice for real, landing it a `t` gap of `RESCALE_STEP * 2 == 8` ahead of
+    // `small` -- the widest gap `merge`/`merge_rescaled` still bridge rather than
+    // discard. Forcing the gap open with manual `decrement()` calls instead (as a
+    // single burst of inserts would need) skews the rank distribution away from what
+    // two genuine rescales produce and made this test's accuracy wildly hash-seed
+    // dependent; letting the gap arise naturally needs a much larger cardinality but
+    // keeps the sketch's internal state realistic.
+    let large_n = 1_000_000u64;
+    let mut large: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 2_000..2_000 + large_n {
+        large.insert(&i);
+    }
+    assert_eq!(large.t - small.t, 8);
+
+    let mut merged = large.clone();
+    assert_eq!(merged.merge_rescaled(small), MergeOutcome::Merged);
+
+    #[allow(clippy::cast_precision_loss)]
+    let actual = (2_000 + large_n) as f64;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - merged.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, count: {}",
+        merged.count()
+    );
+}
+
+#[test]
+fn test_merge_rescaled_discards_beyond_the_max_gap() {
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    b.t = 10;
+    assert_eq!(a.merge_rescaled(b), MergeOutcome::Discarded);
+}
+
+#[test]
+fn test_rank_quantiles() {
+    // Set every substream directly (bypassing the count/rescale bookkeeping in
+    // insert_split) so the value distribution, and thus the expected quantiles, is
+    // known: the first quarter of streams stay at 0 (the default), the next half are
+    // set to 1, and the last quarter to 2.
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let streams = M4096::STREAMS;
+    for stream in streams / 4..streams * 3 / 4 {
+        htb.sketch.set(stream, 1);
+    }
+    for stream in streams * 3 / 4..streams {
+        htb.sketch.set(stream, 2);
+    }
+    let (p25, p50, p75) = htb.rank_quantiles();
+    assert_eq!((p25, p50, p75), (1, 1, 2));
+}
+
+#[test]
+fn test_to_ascii_heatmap_renders_a_known_filled_m64_grid() {
+    let mut htb: HyperTwoBits<M64> = HyperTwoBits::new();
+    for stream in 0..M64::STREAMS {
+        #[allow(clippy::cast_possible_truncation)]
+        htb.sketch.set(stream, (stream % 4) as u8);
+    }
+
+    let expected = [".123.123"; 8].join("\n");
+    assert_eq!(htb.to_ascii_heatmap(), expected);
+}
+
+#[test]
+fn test_insert_split_mirrors_insert_hash() {
+    let hashes: Vec<u64> = (0..10_000u64)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect();
+
+    let mut via_hash: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for &hash in &hashes {
+        via_hash.insert_hash(hash);
+    }
+
+    let mut via_split: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for &hash in &hashes {
+        let stream = (hash >> M4096::IDX_SHIFT) as u32;
+        let rank = (hash & M4096::HASH_MASK).trailing_ones();
+        via_split.insert_split(stream, rank);
+    }
+
+    assert_eq!(via_hash, via_split);
+}
+
+#[test]
+fn test_hash_to_splits_replayed_via_insert_split_matches_direct_insert() {
+    let values: Vec<u64> = (0..5_000u64).collect();
+
+    let mut direct: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for v in &values {
+        direct.insert(v);
+    }
+
+    let router: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let splits = router.hash_to_splits(&values);
+    assert_eq!(splits.len(), values.len());
+    assert_eq!(
+        router.count(),
+        0,
+        "hash_to_splits must not mutate the counter"
+    );
+
+    let mut replayed = router;
+    for (stream, rank) in splits {
+        replayed.insert_split(stream, rank);
+    }
+
+    assert_eq!(direct, replayed);
+}
+
+#[test]
+fn test_with_sampling() {
+    let mut sampled = HyperTwoBits::<M4096>::new().with_sampling(4);
+    let mut full: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..200_000u64 {
+        sampled.insert(&i);
+        full.insert(&i);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let expected = full.count() as f64;
+    #[allow(clippy::cast_precision_loss)]
+    let sampled = sampled.count() as f64;
+    assert!((sampled - expected).abs() / expected < 0.2);
+}
+
+#[test]
+fn test_with_capacity_hint_sensible_t_and_counts() {
+    let htb: HyperTwoBits<M4096> = HyperTwoBits::with_capacity_hint(1_000_000);
+    // 1_000_000 / 4096 ~= 244, log2(244) ~= 7.9, so t should land around 7.
+    assert!(
+        (6..=9).contains(&htb.t),
+        "t should land near the hinted scale, got {}",
+        htb.t
+    );
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::with_capacity_hint(1_000_000);
+    for i in 0..1_000_000u64 {
+        htb.insert(&i);
+    }
+    let actual = 1_000_000.0;
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (actual - htb.count() as f64).abs() / actual;
+    assert!(
+        delta < 0.1,
+        "delta too high: {delta}, count: {}",
+        htb.count()
+    );
+}
+
+#[test]
+fn test_absorb_estimate_from_hbb64_is_close() {
+    use crate::hbb64::HyperBitBit64;
+
+    let mut hbb: HyperBitBit64 = HyperBitBit64::new();
+    for i in 0..50_000u64 {
+        hbb.insert(i);
+    }
+    let hbb_estimate = hbb.count();
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    htb.absorb_estimate_from(&hbb);
+
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (hbb_estimate as f64 - htb.count() as f64).abs() / hbb_estimate as f64;
+    assert!(
+        delta < 0.05,
+        "delta too high: {delta}, hbb: {hbb_estimate}, htb: {}",
+        htb.count()
+    );
+}
+
+/// A deliberately broken hasher that ignores its input and always finishes to the same
+/// value, for [`test_hasher_quality_sample_scores_ahash_well_and_constant_hasher_poorly`].
+#[derive(Default)]
+struct ConstantHasher;
+
+impl std::hash::Hasher for ConstantHasher {
+    fn finish(&self) -> u64 {
+        0x1234_5678_9abc_def0
+    }
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+#[test]
+fn test_hasher_quality_sample_scores_ahash_well_and_constant_hasher_poorly() {
+    let good: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let good_score = good.hasher_quality_sample(10_000);
+    assert!(good_score > 0.8, "ahash scored too low: {good_score}");
+
+    let bad: HyperTwoBits<M4096, std::hash::BuildHasherDefault<ConstantHasher>> =
+        HyperTwoBits::new();
+    let bad_score = bad.hasher_quality_sample(10_000);
+    assert!(
+        bad_score < 0.2,
+        "constant hasher scored too high: {bad_score}"
+    );
+}
+
+#[test]
+fn test_war_and_peace() -> std::io::Result<()> {
+    test_all("data/War_and_Peace.csv", 22668, 0.1, usize::MAX)
+}
+#[test]
+fn test_war_and_peace_100() -> std::io::Result<()> {
+    test_all("data/War_and_Peace.csv", 70, 0.20, 100)
+}
+#[test]
+fn test_war_and_peace_1_000() -> std::io::Result<()> {
+    test_all("data/War_and_Peace.csv", 200, 0.13, 1_000)
+}
+#[test]
+fn test_war_and_peace_10_000() -> std::io::Result<()> {
+    test_all("data/War_and_Peace.csv", 2030, 0.1, 10_000)
+}
+#[test]
+fn test_war_and_peace_100_000() -> std::io::Result<()> {
+    test_all("data/War_and_Peace.csv", 8248, 0.1, 100_000)
+}
+
+#[test]
+fn test_from_sketch_with_known_values() {
+    // Two streams set to 3 (the max value), the rest left at 0, so `count()` is exactly
+    // computable without needing to reach this state via `insert`.
+    let mut values = vec![0u8; M4096::STREAMS as usize];
+    values[0] = 3;
+    values[1] = 3;
+    let sketch = M4096::from_values(&values);
+    assert_eq!(sketch.count(), 2);
+
+    // With so few active substreams at `t == 1`, `HyperTwoBits::count` takes the
+    // small-cardinality path (see `SMALL_CARDINALITY_THRESHOLD`) rather than
+    // `DefaultEstimator`, which would otherwise double this via the `2^t` scale factor.
+    let htb: HyperTwoBits<M4096> = HyperTwoBits::from_sketch(sketch, 1, 2);
+    assert_eq!(htb.count(), 2);
+}
+
+#[cfg(not(feature = "compact"))]
+#[test]
+fn test_recompute_resyncs_count_after_direct_sketch_mutation() {
+    // Start empty: the cached `count` is 0 and stays there until something drives it,
+    // since `sketch_mut` bypasses the incremental bookkeeping `insert` does. Under the
+    // `compact` feature there's no cache to go stale -- `count_components` recomputes
+    // straight from the sketch every time -- so this test only makes sense off it.
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let (_, active_before, _) = htb.count_components();
+    assert_eq!(active_before, 0);
+
+    // Activate a register directly rather than through `insert`.
+    htb.sketch_mut().set(0, 1);
+
+    // The cached `count` hasn't moved yet...
+    let (_, still_stale, _) = htb.count_components();
+    assert_eq!(
+        still_stale, 0,
+        "direct sketch mutation must not update the cached count"
+    );
+
+    // ...until `recompute` re-derives it from the sketch's actual register contents.
+    htb.recompute();
+    let (_, active_after, _) = htb.count_components();
+    assert_eq!(active_after, 1);
+    assert_eq!(active_after, htb.sketch().count());
+}
+
+#[test]
+fn test_recompute_after_manual_register_writes_yields_correct_estimate() {
+    // Bypass `insert` entirely: hand-set every register to a known value so the true
+    // active-stream count is known up front, then confirm `count()` matches the
+    // estimate a normal insert-driven sketch with that many active streams would give,
+    // once `recompute` has resynced the cached `count` field.
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for stream in 0..2_000u32 {
+        htb.sketch_mut().set(stream, 1);
+    }
+    htb.recompute();
+
+    let mut reference: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..1_000_000u64 {
+        reference.insert(&i);
+        if reference.sketch.count() >= 2_000 {
+            break;
+        }
+    }
+
+    assert_eq!(htb.count(), reference.count());
+}
+
+#[cfg(feature = "async-stream")]
+#[test]
+fn test_insert_stream_matches_element_wise_insert() {
+    let values: Vec<u64> = (0..5_000u64).collect();
+
+    let mut streamed: HyperTwoBits<M4096> = HyperTwoBits::new();
+    futures::executor::block_on(streamed.insert_stream(futures::stream::iter(values.clone())));
+
+    let mut one_by_one: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for v in &values {
+        one_by_one.insert(v);
+    }
+
+    assert_eq!(streamed.count(), one_by_one.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(streamed.sketch.val(stream), one_by_one.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_fixed_scale_produces_identical_state_regardless_of_insert_order() {
+    use rand::seq::SliceRandom as _;
+
+    let values: Vec<u64> = (0..2_000u64).collect();
+    let mut shuffled = values.clone();
+    shuffled.shuffle(&mut rand::thread_rng());
+
+    let mut in_order: HyperTwoBits<M4096> = HyperTwoBits::fixed_scale(10);
+    for v in &values {
+        in_order.insert(v);
+    }
+
+    let mut out_of_order: HyperTwoBits<M4096> = HyperTwoBits::fixed_scale(10);
+    for v in &shuffled {
+        out_of_order.insert(v);
+    }
+
+    assert!(!in_order.at_capacity());
+    assert!(!out_of_order.at_capacity());
+    assert_eq!(in_order.count(), out_of_order.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(in_order.sketch.val(stream), out_of_order.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_insert_slice_and_insert_iter_report_active_substream_delta() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+
+    let fresh: Vec<u64> = (0..1_000u64).collect();
+    let gained = htb.insert_slice(&fresh);
+    assert!(
+        gained > 0,
+        "a batch of all-new items must report a positive delta"
+    );
+
+    let repeats = fresh.clone();
+    let gained_repeats = htb.insert_iter(repeats);
+    assert_eq!(
+        gained_repeats, 0,
+        "re-inserting the same items must report no new information"
+    );
+}
+
+#[test]
+fn test_insert_iter_novelty_flags_only_first_occurrence() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+
+    let fresh: Vec<u64> = (0..1_000u64).collect();
+    let mut values = fresh.clone();
+    values.extend(fresh.iter().copied());
+
+    let novelty = htb.insert_iter_novelty(values);
+    assert_eq!(novelty.len(), 2_000);
+
+    // Never a false positive: a repeated element can never be flagged novel, since it
+    // hashes to the same substream/rank a first insert already reached.
+    for &was_novel in &novelty[1_000..] {
+        assert!(!was_novel, "a repeated element must never be flagged novel");
+    }
+    // At least some of the first (all-distinct) half must be flagged novel.
+    assert!(
+        novelty[..1_000].iter().any(|&was_novel| was_novel),
+        "some genuinely new elements must be flagged novel"
+    );
+}
+
+#[test]
+fn test_max_value_matches_substream_bit_width() {
+    use crate::h3b::Sketch as _;
+
+    assert_eq!(M64::MAX_VALUE, 3, "h2b substreams are 2 bits wide");
+    assert_eq!(
+        crate::h3b::M64::MAX_VALUE,
+        7,
+        "h3b substreams are 3 bits wide"
+    );
+}
+
+#[cfg(feature = "fast-math")]
+#[test]
+fn test_fast_ln_within_tolerance() {
+    let inputs: [f64; 11] = [
+        1.01,
+        1.1,
+        1.5,
+        2.0,
+        3.0,
+        5.0,
+        10.0,
+        50.0,
+        100.0,
+        4096.0,
+        1_000_000.0,
+    ];
+    for x in inputs {
+        let exact = x.ln();
+        let approx = super::fast_ln(x);
+        let relative_error = (exact - approx).abs() / exact;
+        assert!(
+            relative_error < 0.001,
+            "fast_ln({x}) = {approx}, expected ~{exact}, relative error {relative_error}"
+        );
+    }
+}
+
+#[cfg(feature = "history")]
+#[test]
+fn test_estimate_quantile_median_falls_between_ramp_endpoints() {
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..10_000u64 {
+        htb.insert(&i);
+    }
+
+    let min = htb.estimate_quantile(0.0);
+    let median = htb.estimate_quantile(0.5);
+    let max = htb.estimate_quantile(1.0);
+
+    assert!(min > 0, "min snapshot should reflect a nonempty history");
+    assert!(
+        min <= median && median <= max,
+        "median {median} should fall between min {min} and max {max}"
+    );
+    // Every insert on a monotone ramp raises `count()`, so the most recent
+    // `HISTORY_CAPACITY` snapshots -- everything `estimate_quantile` can see -- are
+    // strictly the highest ones the sketch ever reported.
+    assert_eq!(max, htb.count());
+}
+
+#[cfg(feature = "history")]
+#[test]
+fn test_estimate_quantile_before_any_insert_is_zero() {
+    let htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    assert_eq!(htb.estimate_quantile(0.5), 0);
 }
@@ -4,7 +4,7 @@ mod tests;
 
 use std::hash::BuildHasher;
 
-pub use sketch::{Sketch, M1024, M128, M2048, M256, M4096, M512, M64};
+pub use sketch::{Sketch, M1024, M128, M2048, M256, M4096, M512, M64, M8192};
 
 use crate::AHasherDefaultBuilder;
 
@@ -85,8 +85,10 @@ impl<HASH: BuildHasher + Default, BITS: Sketch> HyperThreeBits<BITS, HASH> {
             // Merg sketches
             self.sketch.merge(&other.sketch);
         } else {
-            // merge the high bits of other into the low bits of self
-            self.sketch.merge_high_into_lo(&other.sketch);
+            // downgrade other's values by however many rescale steps self is ahead,
+            // then combine into self
+            let steps = (self.t - other.t) / 4;
+            self.sketch.merge_high_into_lo(&other.sketch, steps);
         }
         // update the count
         self.count = self.sketch.count();
@@ -147,6 +149,143 @@ impl<HASH: BuildHasher + Default, BITS: Sketch> HyperThreeBits<BITS, HASH> {
         }
     }
 
+    #[inline]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Inserts a value keyed by two independent 64-bit hashes concatenated into a
+    /// 128-bit rank, so tiers beyond `t + 32` are actually reachable: a single `u64`
+    /// hash supplies at most 64 trailing one-bits total, and `HASH_MASK` carves some of
+    /// those off for the stream index, so [`Self::insert_hash`] can basically
+    /// never set value 6 (needs `t + 64`) and never value 7 (`t + 128`) at all. The
+    /// 128-bit rank here raises that ceiling enough for value 6 to be reachable; value 7
+    /// would need the full 128 bits free for rank, which still isn't possible once some
+    /// of them are spent on the stream index.
+    ///
+    /// `hi` supplies the stream index (same as [`Self::insert_hash`]) and, once masked,
+    /// the high half of the rank; `lo` supplies the low half. Both must come from
+    /// independent hashes of the same logical value — reusing one hash for both, or
+    /// feeding correlated hashes, reproduces `insert_hash`'s ceiling instead of lifting
+    /// it. This costs a second hash computation per insert versus [`Self::insert`].
+    pub fn insert_hash_pair(&mut self, hi: u64, lo: u64) {
+        let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
+        // use most significant bits of `hi` for the stream index, as `insert_hash` does
+        let stream_index: u32 = (hi >> BITS::IDX_SHIFT) as u32;
+        let rank: u128 = (u128::from(hi & BITS::HASH_MASK) << 64) | u128::from(lo);
+
+        if rank.trailing_ones() >= self.t && self.sketch.val(stream_index) < 1 {
+            self.count += 1;
+            self.sketch.set(stream_index, 1);
+        }
+        // 2^4
+        if rank.trailing_ones() >= self.t + 4 && self.sketch.val(stream_index) < 2 {
+            self.sketch.set(stream_index, 2);
+        }
+
+        // 2^8
+        if rank.trailing_ones() >= self.t + 8 && self.sketch.val(stream_index) < 3 {
+            self.sketch.set(stream_index, 3);
+        }
+
+        // 2^16
+        if rank.trailing_ones() >= self.t + 16 && self.sketch.val(stream_index) < 4 {
+            self.sketch.set(stream_index, 4);
+        }
+
+        // 2^32
+        if rank.trailing_ones() >= self.t + 32 && self.sketch.val(stream_index) < 5 {
+            self.sketch.set(stream_index, 5);
+        }
+
+        // 2^64
+        if rank.trailing_ones() >= self.t + 64 && self.sketch.val(stream_index) < 6 {
+            self.sketch.set(stream_index, 6);
+        }
+        // 2^128
+        if rank.trailing_ones() >= self.t + 128 && self.sketch.val(stream_index) < 7 {
+            self.sketch.set(stream_index, 7);
+        }
+
+        if self.count >= threshold {
+            self.count = self.sketch.decrement();
+            self.t += 4;
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    /// Inserts a batch of pre-hashed 128-bit ranks, the bulk companion to
+    /// [`Self::insert_hash_pair`] for callers that already have (or can cheaply produce)
+    /// combined 128-bit hashes rather than the two independent 64-bit halves that method
+    /// takes. As with [`Self::insert_hash_pair`], each `hash`'s top bits carry the
+    /// stream index and the rest feed the rank ladder -- and the same ceiling applies:
+    /// with some bits spent on the index, a 128-bit rank still can't clear the 128
+    /// trailing ones value 7 needs, but comfortably clears the 64 value 6 needs. The
+    /// rescale threshold is checked once after the whole batch rather than after each
+    /// element, the same delayed-count-update tradeoff [`crate::h2b::HyperTwoBits::insert_array`]
+    /// makes.
+    pub fn insert_hashes128(&mut self, hashes: &[u128]) {
+        let threshold: u32 = const { (Self::ALPHA * BITS::STREAMS as f64) as u32 };
+        let idx_bits = 64 - BITS::IDX_SHIFT;
+        let rank_bits = 128 - idx_bits;
+        let rank_mask: u128 = (1u128 << rank_bits) - 1;
+
+        for &hash in hashes {
+            let stream_index = (hash >> rank_bits) as u32;
+            let rank = hash & rank_mask;
+
+            if rank.trailing_ones() >= self.t && self.sketch.val(stream_index) < 1 {
+                self.count += 1;
+                self.sketch.set(stream_index, 1);
+            }
+            // 2^4
+            if rank.trailing_ones() >= self.t + 4 && self.sketch.val(stream_index) < 2 {
+                self.sketch.set(stream_index, 2);
+            }
+
+            // 2^8
+            if rank.trailing_ones() >= self.t + 8 && self.sketch.val(stream_index) < 3 {
+                self.sketch.set(stream_index, 3);
+            }
+
+            // 2^16
+            if rank.trailing_ones() >= self.t + 16 && self.sketch.val(stream_index) < 4 {
+                self.sketch.set(stream_index, 4);
+            }
+
+            // 2^32
+            if rank.trailing_ones() >= self.t + 32 && self.sketch.val(stream_index) < 5 {
+                self.sketch.set(stream_index, 5);
+            }
+
+            // 2^64
+            if rank.trailing_ones() >= self.t + 64 && self.sketch.val(stream_index) < 6 {
+                self.sketch.set(stream_index, 6);
+            }
+            // 2^128
+            if rank.trailing_ones() >= self.t + 128 && self.sketch.val(stream_index) < 7 {
+                self.sketch.set(stream_index, 7);
+            }
+        }
+
+        if self.count >= threshold {
+            self.count = self.sketch.decrement();
+            self.t += 4;
+        }
+    }
+
+    #[must_use]
+    /// Builds a counter directly from a precomputed hash array, skipping [`Self::new`]'s
+    /// hasher entirely. Feeding the same `hashes` slice to
+    /// [`crate::h2b::HyperTwoBits::from_hash_stream`], this, and an HLL adapter removes
+    /// hasher choice as a confound when comparing estimators head to head, since all
+    /// three then see byte-identical input regardless of what each would otherwise hash
+    /// values with.
+    pub fn from_hash_stream(hashes: &[u64]) -> Self {
+        let mut sketch = Self::new();
+        for &hash in hashes {
+            sketch.insert_hash(hash);
+        }
+        sketch
+    }
+
     /// returns the estimated count. This function is non destructive
     /// and can be called multiple times without changing the state of the counter
     #[inline]
@@ -156,4 +295,169 @@ impl<HASH: BuildHasher + Default, BITS: Sketch> HyperThreeBits<BITS, HASH> {
         let bias: f64 = (1.0 / beta).ln();
         ((2.0_f64.powf(f64::from(self.t))) * f64::from(BITS::STREAMS) * bias) as u64
     }
+
+    #[must_use]
+    /// Lossy conversion down to a [`crate::h2b::HyperTwoBits`] using the two-bit-per-substream
+    /// sketch `TWO` with the same substream count as `BITS`, for users who over-provisioned
+    /// three bits and want the smaller footprint for storage. Values `4..=7`, which only
+    /// three bits can represent, are clamped down to `3`; the low two planes are copied as
+    /// is, so high-cardinality precision beyond what two bits can express is lost.
+    /// # Panics
+    /// Debug-asserts that `TWO::STREAMS == BITS::STREAMS`.
+    pub fn into_two_bits<TWO: crate::h2b::Sketch>(self) -> crate::h2b::HyperTwoBits<TWO, HASH> {
+        debug_assert_eq!(
+            TWO::STREAMS,
+            BITS::STREAMS,
+            "substream counts must match to convert"
+        );
+        let mut sketch = TWO::default();
+        for stream in 0..BITS::STREAMS {
+            sketch.set(stream, self.sketch.val(stream).min(3));
+        }
+        crate::h2b::HyperTwoBits::from_converted(self.hash, sketch, self.t)
+    }
+
+    /// Yields this counter's raw sketch plane words, see [`Sketch::plane_words`] for the
+    /// exact ordering. Pairs with [`Self::from_raw_planes`] for columnar storage.
+    pub fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.sketch.plane_words()
+    }
+
+    #[must_use]
+    /// Reconstructs a counter from `t`, `count`, and the exact word order
+    /// [`Self::plane_words`] yields, using the default hasher.
+    /// # Panics
+    /// Panics if `words` doesn't yield exactly as many words as [`Self::plane_words`] would.
+    pub fn from_raw_planes(words: impl Iterator<Item = u128>, t: u32, count: u32) -> Self {
+        Self {
+            hash: HASH::default(),
+            sketch: BITS::from_raw_planes(words),
+            count,
+            t,
+        }
+    }
+
+    /// Format version with no checksum, written by [`Self::to_bytes_without_checksum`].
+    const FORMAT_VERSION: u8 = 1;
+    /// Format version with an appended CRC-32, written by [`Self::to_bytes`].
+    const FORMAT_VERSION_CHECKSUMMED: u8 = 2;
+
+    #[must_use]
+    /// Serializes the sketch the same way as [`Self::to_bytes_without_checksum`], then
+    /// appends a little-endian `u32` CRC-32 of those bytes. See
+    /// [`crate::h2b::HyperTwoBits::to_bytes`] for the rationale.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = self.to_bytes_without_checksum();
+        buf[0] = Self::FORMAT_VERSION_CHECKSUMMED;
+        let crc = crate::h2b::crc32(&buf);
+        buf.extend_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    #[must_use]
+    /// Serializes the sketch into a versioned byte buffer, without a checksum: a
+    /// version byte, `t` and `count` as little-endian `u32`s, followed by each of
+    /// [`Self::plane_words`]'s `u128` words, each written little-endian regardless of
+    /// host architecture. The hasher itself is not serialized, so the same
+    /// `HASH`/`BITS` types must be used to interpret the result. Prefer
+    /// [`Self::to_bytes`] unless the 4 checksum bytes matter for your storage budget.
+    pub fn to_bytes_without_checksum(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(9 + self.plane_words().count() * 16);
+        buf.push(Self::FORMAT_VERSION);
+        buf.extend_from_slice(&self.t.to_le_bytes());
+        buf.extend_from_slice(&self.count.to_le_bytes());
+        for word in self.plane_words() {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Deserializes a sketch previously written by [`Self::to_bytes`] or
+    /// [`Self::to_bytes_without_checksum`].
+    /// # Errors
+    /// Returns [`crate::h2b::DecodeError::UnsupportedVersion`] if the version byte is
+    /// not recognized, [`crate::h2b::DecodeError::Truncated`] if `bytes` is too short
+    /// for the version it claims to be, or
+    /// [`crate::h2b::DecodeError::ChecksumMismatch`] if the checksummed format's CRC-32
+    /// doesn't match its payload.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, crate::h2b::DecodeError> {
+        match bytes.first() {
+            Some(&Self::FORMAT_VERSION) => Self::decode_v1(&bytes[1..]),
+            Some(&Self::FORMAT_VERSION_CHECKSUMMED) => Self::decode_v2(bytes),
+            Some(&other) => Err(crate::h2b::DecodeError::UnsupportedVersion(other)),
+            None => Err(crate::h2b::DecodeError::Truncated),
+        }
+    }
+
+    /// Verifies the trailing CRC-32 before delegating to [`Self::decode_v1`] for the
+    /// rest of the payload.
+    fn decode_v2(bytes: &[u8]) -> Result<Self, crate::h2b::DecodeError> {
+        let split = bytes.len().saturating_sub(4);
+        let (payload, crc_bytes) = bytes
+            .split_at_checked(split)
+            .ok_or(crate::h2b::DecodeError::Truncated)?;
+        if crc_bytes.len() != 4 || payload.is_empty() {
+            return Err(crate::h2b::DecodeError::Truncated);
+        }
+        let expected = u32::from_le_bytes(crc_bytes.try_into().expect("checked length above"));
+        if crate::h2b::crc32(payload) != expected {
+            return Err(crate::h2b::DecodeError::ChecksumMismatch);
+        }
+        Self::decode_v1(&payload[1..])
+    }
+
+    fn decode_v1(bytes: &[u8]) -> Result<Self, crate::h2b::DecodeError> {
+        let (t_bytes, rest) = bytes
+            .split_at_checked(4)
+            .ok_or(crate::h2b::DecodeError::Truncated)?;
+        let (count_bytes, plane_bytes) = rest
+            .split_at_checked(4)
+            .ok_or(crate::h2b::DecodeError::Truncated)?;
+
+        let expected_words = BITS::default().plane_words().count();
+        if plane_bytes.len() != expected_words * 16 {
+            return Err(crate::h2b::DecodeError::Truncated);
+        }
+        let words = plane_bytes
+            .chunks_exact(16)
+            .map(|chunk| u128::from_le_bytes(chunk.try_into().expect("chunked to 16 bytes")))
+            .collect::<Vec<_>>();
+
+        let t = u32::from_le_bytes(t_bytes.try_into().expect("checked length above"));
+        let count = u32::from_le_bytes(count_bytes.try_into().expect("checked length above"));
+        Ok(Self::from_raw_planes(words.into_iter(), t, count))
+    }
+}
+
+/// Bits each h3b substream occupies in a sketch's storage (`0..=7`, one of eight ladder
+/// values). Mirrors [`crate::h2b::BITS_PER_STREAM`], which is `2` for h2b's four-value
+/// ladder.
+pub const BITS_PER_STREAM: u32 = 3;
+
+/// Returns the number of bits an `M`-substream sketch occupies in storage (`m *
+/// BITS_PER_STREAM`). See [`crate::h2b::theoretical_bits`] for the h2b equivalent and
+/// its role in putting sketches on equal memory footing for accuracy comparisons.
+#[must_use]
+pub const fn theoretical_bits(m: usize) -> usize {
+    m * BITS_PER_STREAM as usize
 }
+
+// Same guarantee as `h2b::HyperTwoBits`: no interior mutability, so this is
+// `Send`/`Sync` for any `Send + Sync` `SKETCH`/`HASH`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HyperThreeBits<M4096>>();
+};
+
+// Pins the stack size of each sketch size so a layout regression (e.g. an added field)
+// fails the build instead of surfacing as a surprise at runtime.
+const _: () = {
+    assert!(std::mem::size_of::<HyperThreeBits<M64>>() == 32);
+    assert!(std::mem::size_of::<HyperThreeBits<M128>>() == 64);
+    assert!(std::mem::size_of::<HyperThreeBits<M256>>() == 112);
+    assert!(std::mem::size_of::<HyperThreeBits<M512>>() == 208);
+    assert!(std::mem::size_of::<HyperThreeBits<M1024>>() == 400);
+    assert!(std::mem::size_of::<HyperThreeBits<M2048>>() == 784);
+    assert!(std::mem::size_of::<HyperThreeBits<M4096>>() == 1552);
+    assert!(std::mem::size_of::<HyperThreeBits<M8192>>() == 3088);
+};
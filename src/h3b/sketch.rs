@@ -1,9 +1,14 @@
-const MAX_VALUE: u8 = 7;
 /// Sketch storage for `HyperTwoBits` the trait is used
 /// to implement optimized storage structs for each value of M
 /// this allows us let the compilare avoid know exaclty what M is
 /// and avoid conditionals, loops and branches.
 pub trait Sketch: Default {
+    /// Largest value a single substream can hold, i.e. the top of the ladder
+    /// `insert`/`decrement` climb and drop along. `7` here since h3b substreams are
+    /// 3 bits wide; `crate::h2b::Sketch::MAX_VALUE` is `3` for its 2-bit substreams.
+    /// Lets generic code (estimators, histograms, conversions) work across both
+    /// without hardcoding either width.
+    const MAX_VALUE: u8 = 7;
     /// Number of substreams
     const STREAMS: u32;
     /// bitmask for x, the most significant bits n bits are used so that 2^n = M
@@ -32,10 +37,61 @@ pub trait Sketch: Default {
     fn count(&self) -> u32;
     /// Merges the sketch with another sketch by oring the values
     fn merge(&mut self, other: &Self);
-    /// Merges sketches that differ in T by the following rules:
-    /// - self.lo = self.lo | other.hi
-    /// - self.hi remains unchanged
-    fn merge_high_into_lo(&mut self, other: &Self);
+    /// Merges a sketch whose `t` is `steps` rescale steps behind this one (each rescale
+    /// step is `+4`, see [`crate::h3b::HyperThreeBits::merge`]): downgrades each of
+    /// `other`'s substream values by the number of ladder tiers `steps` rescales would
+    /// have knocked off (see [`downgrade`]) before combining. This can't be done by
+    /// shifting bit-planes the way `h2b`'s two-bit ladder does: `h3b`'s tiers are spaced
+    /// `[0, 4, 8, 16, 32, 64, 128]` past `t`, uniform only for the first two, so a
+    /// single bit-plane shift both over-corrects low values and under-corrects high
+    /// ones. `self` keeps its own value wherever it's already at least as high as
+    /// `other`'s downgraded one.
+    #[inline]
+    fn merge_high_into_lo(&mut self, other: &Self, steps: u32) {
+        for stream in 0..Self::STREAMS {
+            let downgraded = downgrade(other.val(stream), steps);
+            if downgraded > self.val(stream) {
+                self.set(stream, downgraded);
+            }
+        }
+    }
+    /// Yields this sketch's raw plane words, high-plane-first then middle then low, one
+    /// triple per underlying register (the single-struct backings `M64`/`M128` have
+    /// just one implicit register covering all `STREAMS` substreams, so they yield
+    /// exactly 3 words; the vectored `M256`..`M8192` backings yield 3 words per
+    /// register). Pairs with [`Self::from_raw_planes`] for columnar, one-value-per-plane
+    /// storage: persist each yielded word to its own column, in order, and readers can
+    /// reconstruct without needing to know anything about this type beyond the order.
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_;
+    /// Reconstructs a sketch from the exact word order [`Self::plane_words`] yields.
+    /// # Panics
+    /// Panics if `words` doesn't yield exactly as many words as [`Self::plane_words`]
+    /// would for this sketch type.
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self;
+}
+
+/// Downgrades a value that was measured against a `t` that's `steps` rescale steps
+/// (each `+4`) behind this sketch's current `t`, per `h3b`'s ladder offsets `[0, 4, 8,
+/// 16, 32, 64, 128]`. Computed as a fixed table rather than derived at runtime, since
+/// [`Sketch::merge_high_into_lo`]'s only callsite (`HyperThreeBits::merge`) never
+/// bridges more than two rescale steps.
+fn downgrade(v: u8, steps: u32) -> u8 {
+    debug_assert!(
+        steps <= 2,
+        "h3b's merge only ever bridges up to two rescale steps"
+    );
+    match steps {
+        0 => v,
+        1 => v.saturating_sub(1),
+        _ => match v {
+            0..=2 => 0,
+            3 => 1,
+            4 => 3,
+            5 => 4,
+            6 => 5,
+            _ => 6,
+        },
+    }
 }
 
 /// M = 64, using two 64 bit integers to store the sketch
@@ -66,7 +122,7 @@ impl Sketch for M64 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         // split value in it's respective bits
         let value = u64::from(value);
         let value_high_bit = (value >> 2) & 1;
@@ -108,11 +164,23 @@ impl Sketch for M64 {
         self.middle |= other.middle;
         self.low |= other.low;
     }
-
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.low |= other.middle;
-        self.middle |= other.high;
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        [self.high, self.middle, self.low]
+            .into_iter()
+            .map(u128::from)
+    }
+    #[inline]
+    fn from_raw_planes(mut words: impl Iterator<Item = u128>) -> Self {
+        let high = words.next().expect("missing high plane word");
+        let middle = words.next().expect("missing middle plane word");
+        let low = words.next().expect("missing low plane word");
+        assert!(words.next().is_none(), "too many plane words for M64");
+        Self {
+            high: u64::try_from(high).expect("high plane word must fit in u64 for M64"),
+            middle: u64::try_from(middle).expect("middle plane word must fit in u64 for M64"),
+            low: u64::try_from(low).expect("low plane word must fit in u64 for M64"),
+        }
     }
 }
 
@@ -151,7 +219,7 @@ impl Sketch for M128 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         // split value in it's respective bits
         let value = u128::from(value);
         let value_high_bit = (value >> 2) & 1;
@@ -176,7 +244,7 @@ impl Sketch for M128 {
     #[inline]
     fn count(&self) -> u32 {
         let used_streams = self.middle | self.low | self.high;
-        used_streams.count_ones()
+        crate::register::popcount128(used_streams)
     }
     #[inline]
     fn merge(&mut self, other: &Self) {
@@ -185,122 +253,60 @@ impl Sketch for M128 {
         self.low |= other.low;
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.low |= other.middle;
-        self.middle |= other.high;
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        [self.high, self.middle, self.low].into_iter()
+    }
+    #[inline]
+    fn from_raw_planes(mut words: impl Iterator<Item = u128>) -> Self {
+        let high = words.next().expect("missing high plane word");
+        let middle = words.next().expect("missing middle plane word");
+        let low = words.next().expect("missing low plane word");
+        assert!(words.next().is_none(), "too many plane words for M128");
+        Self { low, middle, high }
     }
 }
 
-/// We use a register to store hi and low bits together
-/// to optimize for cache locallity when compiting inside
-/// a vectored sketch
-#[derive(Default, Clone, Copy, Debug, Hash, Eq, PartialEq)]
-#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
-struct BitRegister {
-    high: u128,
-    middle: u128,
-    low: u128,
-}
-/// Generic scatch using `REGISTERS` 128 bit `HiLoRegister`
+/// Generic vectored sketch built out of shared three-plane [`crate::register::BitRegister`]s,
 /// so the total M for the sketch is `REGISTERS` * 128.
 ///
 /// This is not meant to be used directly instead it serves as
 /// a base for the other vectored sketches
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
 #[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
 pub struct M128Reg<const REGISTERS: usize> {
-    registers: [BitRegister; REGISTERS],
-}
-
-impl<const REGISTERS: usize> Default for M128Reg<REGISTERS> {
-    fn default() -> Self {
-        Self {
-            registers: [BitRegister {
-                high: 0,
-                middle: 0,
-                low: 0,
-            }; REGISTERS],
-        }
-    }
+    inner: crate::register::M128Reg<REGISTERS, 3>,
 }
 
 impl<const REGISTERS: usize> M128Reg<REGISTERS> {
-    const REG_SIZE: usize = 128;
     #[inline]
     fn val(&self, stream: u32) -> u8 {
-        // Calculate the index in the sketch vector
-        let register_index = stream as usize / Self::REG_SIZE;
-        // calculate the left over index into the sketc
-        let bit_index = stream as usize % Self::REG_SIZE;
-        let high_bit = ((self.registers[register_index].high >> bit_index) & 1) as u8;
-        // Calculate the high bit
-        let middle_bit = ((self.registers[register_index].middle >> bit_index) & 1) as u8;
-        // Calculate the low bit
-        let low_bit = ((self.registers[register_index].low >> bit_index) & 1) as u8;
-        (high_bit << 2) | (middle_bit << 1) | low_bit
+        self.inner.val(stream)
     }
 
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
-        debug_assert!(value <= MAX_VALUE);
-        // Calculate the index in the sketch vector
-        let register_index = stream as usize / Self::REG_SIZE;
-        // calculate the left over index into the sketc
-        let bit_index = stream as usize % Self::REG_SIZE;
-
-        // split value in it's respective bits
-        let value = u128::from(value);
-        let value_high_bit = (value >> 2) & 1;
-        let value_middle_bit = (value >> 1) & 1;
-        let value_low_bit = value & 1;
-
-        // reset all bits at index
-        self.registers[register_index].high &= !(1 << bit_index);
-        self.registers[register_index].middle &= !(1 << bit_index);
-        self.registers[register_index].low &= !(1 << bit_index);
-
-        // set the bits at index to the value
-        self.registers[register_index].high |= value_high_bit << bit_index;
-        self.registers[register_index].middle |= value_middle_bit << bit_index;
-        self.registers[register_index].low |= value_low_bit << bit_index;
+        debug_assert!(value <= 7);
+        self.inner.set(stream, value);
     }
-    // #[inline]
-    // fn decrement(&mut self) -> u32 {
-    //     let mut count = 0;
-    //     // Decrement by decrementing each register
-    //     for s in &mut self.s {
-    //         count += s.hi.count_ones();
-    //         s.lo = s.hi & !s.lo;
-    //         s.hi &= !s.lo;
-    //     }
-    //     count
-    // }
     #[inline]
     fn count(&self) -> u32 {
-        let mut count = 0;
-        // Count the number of active substreams by counting them for each register
-        // and summing them up
-        for register in self.registers {
-            count += (register.middle | register.low | register.high).count_ones();
-        }
-        count
+        self.inner.count()
     }
     #[inline]
     fn merge(&mut self, other: &Self) {
-        // Merge by merging each register
-        for (self_register, other_register) in self.registers.iter_mut().zip(other.registers.iter())
-        {
-            self_register.high |= other_register.high;
-            self_register.middle |= other_register.middle;
-            self_register.low |= other_register.low;
-        }
+        #[cfg(feature = "simd")]
+        self.inner.merge_simd(&other.inner);
+        #[cfg(not(feature = "simd"))]
+        self.inner.merge(&other.inner);
+    }
+    #[inline]
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.inner.plane_words()
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        // Merge by merging each register
-        for (self_register, b) in self.registers.iter_mut().zip(other.registers.iter()) {
-            self_register.low |= b.middle;
-            self_register.middle |= b.high;
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self {
+            inner: crate::register::M128Reg::from_raw_planes(words),
         }
     }
 }
@@ -323,7 +329,7 @@ impl Sketch for M256 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -339,8 +345,12 @@ impl Sketch for M256 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
@@ -362,7 +372,7 @@ impl Sketch for M512 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -378,8 +388,12 @@ impl Sketch for M512 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
@@ -401,7 +415,7 @@ impl Sketch for M1024 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -417,8 +431,12 @@ impl Sketch for M1024 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
@@ -440,7 +458,7 @@ impl Sketch for M2048 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -456,8 +474,12 @@ impl Sketch for M2048 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
@@ -479,7 +501,7 @@ impl Sketch for M4096 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -495,19 +517,23 @@ impl Sketch for M4096 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
-/// M = 4096 Sketch Implementation
+/// M = 8192 Sketch Implementation
 pub type M8192 = M128Reg<64>;
 
 impl Sketch for M8192 {
-    const STREAMS: u32 = 4096;
+    const STREAMS: u32 = 8192;
     const HASH_MASK: u64 =
-        0b0000_0000_0000_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111;
-    const IDX_SHIFT: u32 = 52;
+        0b0000_0000_0000_0111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111;
+    const IDX_SHIFT: u32 = 51;
 
     #[inline]
     fn val(&self, stream: u32) -> u8 {
@@ -518,7 +544,7 @@ impl Sketch for M8192 {
     #[inline]
     fn set(&mut self, stream: u32, value: u8) {
         debug_assert!(stream < Self::STREAMS);
-        debug_assert!(value <= MAX_VALUE);
+        debug_assert!(value <= Self::MAX_VALUE);
         self.set(stream, value);
     }
     // #[inline]
@@ -534,8 +560,12 @@ impl Sketch for M8192 {
         self.merge(other);
     }
     #[inline]
-    fn merge_high_into_lo(&mut self, other: &Self) {
-        self.merge_high_into_lo(other);
+    fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.plane_words()
+    }
+    #[inline]
+    fn from_raw_planes(words: impl Iterator<Item = u128>) -> Self {
+        Self::from_raw_planes(words)
     }
 }
 
@@ -606,4 +636,8 @@ mod tests {
     fn test_m4096() {
         test::<M4096>();
     }
+    #[test]
+    fn test_m8192() {
+        test::<M8192>();
+    }
 }
@@ -1,37 +1,11 @@
-use super::{HyperThreeBits, Sketch, M4096};
+use super::{HyperThreeBits, Sketch, M256, M4096, M64};
 
 use std::io::{BufRead, BufReader};
 
 use hyperloglog::HyperLogLog;
 
-#[test]
-fn htb64_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M64>>(), 32);
-}
-#[test]
-fn htb128_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M128>>(), 64);
-}
-#[test]
-fn htb256_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M256>>(), 112);
-}
-#[test]
-fn htb512_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M512>>(), 208);
-}
-#[test]
-fn htb1024_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M1024>>(), 400);
-}
-#[test]
-fn htb2048_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M2048>>(), 784);
-}
-#[test]
-fn htb4096_size() {
-    assert_eq!(std::mem::size_of::<HyperThreeBits<super::M4096>>(), 1552);
-}
+// Layout sizes are pinned via compile-time `const` assertions in `h3b.rs` itself now,
+// so a regression fails to compile rather than surfacing here at test time.
 
 #[allow(
     clippy::cast_precision_loss,
@@ -161,3 +135,231 @@ fn test_war_and_peace_10_000() -> std::io::Result<()> {
 fn test_war_and_peace_100_000() -> std::io::Result<()> {
     test_all("data/War_and_Peace.csv", 8248, 0.1, 100_000)
 }
+
+#[test]
+fn test_into_two_bits_estimate_within_delta() {
+    let mut h3b: HyperThreeBits<M4096> = HyperThreeBits::new();
+    for i in 0..20_000u64 {
+        h3b.insert(&i);
+    }
+    let original = h3b.count();
+    let h2b = h3b.into_two_bits::<crate::h2b::M4096>();
+    #[allow(clippy::cast_precision_loss)]
+    let delta = (original as f64 - h2b.count() as f64).abs() / original as f64;
+    assert!(
+        delta < 0.15,
+        "delta too high: {delta}, original: {original}, converted: {}",
+        h2b.count()
+    );
+}
+
+#[test]
+fn test_merge_high_into_lo_downgrades_by_correct_number_of_steps() {
+    // `a` is two rescale steps (2 * 4 = 8) ahead of `b`'s `t`, so merging must downgrade
+    // each of `b`'s values by two ladder tiers, not by a single bit-plane shift.
+    let mut a: HyperThreeBits<M4096> = HyperThreeBits::new();
+    a.t = 9;
+    a.sketch.set(0, 7);
+    a.sketch.set(1, 4);
+    a.count = a.sketch.count();
+
+    let mut b: HyperThreeBits<M4096> = HyperThreeBits::new();
+    b.t = 1;
+    b.sketch.set(0, 3);
+    b.sketch.set(1, 6);
+    b.sketch.set(2, 5);
+    b.count = b.sketch.count();
+
+    a.merge(b);
+
+    // stream 0: a's own 7 is already the max possible value, so it wins outright.
+    assert_eq!(a.sketch.val(0), 7);
+    // stream 1: a had 4, b's 6 downgrades two steps to 5, so b's downgraded value wins.
+    assert_eq!(a.sketch.val(1), 5);
+    // stream 2: a had 0, b's 5 downgrades two steps to 4.
+    assert_eq!(a.sketch.val(2), 4);
+}
+
+#[test]
+fn test_plane_words_round_trip() {
+    let mut h3b: HyperThreeBits<M4096> = HyperThreeBits::new();
+    for i in 0..20_000u64 {
+        h3b.insert(&i);
+    }
+
+    let words: Vec<u128> = h3b.plane_words().collect();
+    let rebuilt: HyperThreeBits<M4096> =
+        HyperThreeBits::from_raw_planes(words.into_iter(), h3b.t, h3b.count);
+
+    assert_eq!(h3b.count(), rebuilt.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(h3b.sketch.val(stream), rebuilt.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_to_bytes_round_trip() {
+    let mut h3b: HyperThreeBits<M4096> = HyperThreeBits::new();
+    for i in 0..20_000u64 {
+        h3b.insert(&i);
+    }
+
+    let bytes = h3b.to_bytes();
+    let rebuilt: HyperThreeBits<M4096> = HyperThreeBits::from_bytes(&bytes).unwrap();
+
+    assert_eq!(h3b.count(), rebuilt.count());
+    for stream in 0..M4096::STREAMS {
+        assert_eq!(h3b.sketch.val(stream), rebuilt.sketch.val(stream));
+    }
+}
+
+#[test]
+fn test_insert_hash_pair_reaches_value_unreachable_via_single_hash() {
+    // A single `u64` hash's rank bits are `HASH_MASK`-sized (56 bits for `M256`, after
+    // 8 are carved off for the stream index), so `insert_hash` can never reach the 64
+    // trailing ones value 6 needs. Concatenating a second, independent hash's 64 bits
+    // onto the rank easily clears it.
+    let mut h3b: HyperThreeBits<M256> = HyperThreeBits::new();
+    // zeroing the index bits of `hi` (everything above `HASH_MASK`) picks stream 0;
+    // the masked part of `hi` plus all of `lo` being all-ones gives a 120-trailing-one
+    // rank, comfortably past the `t + 64` value-6 threshold at `t`'s initial value of 1.
+    h3b.insert_hash_pair(M256::HASH_MASK, u64::MAX);
+
+    assert_eq!(h3b.sketch.val(0), 6);
+}
+
+#[test]
+fn test_insert_hashes128_batch_sets_high_register_values() {
+    // Same crafted rank as `test_insert_hash_pair_reaches_value_unreachable_via_single_hash`,
+    // but pre-combined into one `u128` and inserted as a batch, exercising
+    // `insert_hashes128`'s single end-of-batch threshold check instead of a per-element
+    // one.
+    let mut h3b: HyperThreeBits<M256> = HyperThreeBits::new();
+    let hash: u128 = (u128::from(M256::HASH_MASK) << 64) | u128::from(u64::MAX);
+    h3b.insert_hashes128(&[hash, hash, hash]);
+
+    assert_eq!(h3b.sketch.val(0), 6);
+    assert!(
+        h3b.count() > 0,
+        "estimate should be sensible (nonzero) after activating a substream"
+    );
+}
+
+#[test]
+fn test_from_hash_stream_builds_byte_identical_sketches() {
+    let hashes: Vec<u64> = (0..10_000u64)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect();
+
+    let a: HyperThreeBits<M256> = HyperThreeBits::from_hash_stream(&hashes);
+    let b: HyperThreeBits<M256> = HyperThreeBits::from_hash_stream(&hashes);
+
+    for stream in 0..M256::STREAMS {
+        assert_eq!(a.sketch.val(stream), b.sketch.val(stream));
+    }
+    assert_eq!(a.t, b.t);
+    assert_eq!(a.count, b.count);
+    assert_eq!(a.count(), b.count());
+}
+
+#[test]
+fn test_to_bytes_is_little_endian() {
+    // Two streams set to the max value 7, so the resulting plane words are exact,
+    // known constants we can check byte-for-byte instead of merely round-tripping.
+    let mut h3b: HyperThreeBits<super::M64> = HyperThreeBits::new();
+    h3b.sketch.set(0, 7);
+    h3b.sketch.set(1, 7);
+    h3b.t = 5;
+    h3b.count = h3b.sketch.count();
+
+    let bytes = h3b.to_bytes_without_checksum();
+
+    // version byte, then `t` and `count` as little-endian u32s.
+    assert_eq!(bytes[0], 1);
+    assert_eq!(&bytes[1..5], &5u32.to_le_bytes());
+    assert_eq!(&bytes[5..9], &h3b.count.to_le_bytes());
+
+    // Each plane word is written little-endian regardless of host architecture: the
+    // low byte of a word comes first in the buffer, so decoding it back with
+    // `from_le_bytes` on any target (including simulated big-endian ones, which we
+    // model here with `swap_bytes` since this sandbox has no BE target to build for)
+    // must reproduce the original word.
+    for chunk in bytes[9..].chunks_exact(16) {
+        let word = u128::from_le_bytes(chunk.try_into().unwrap());
+        let round_tripped_on_be = u128::from_be_bytes(chunk.try_into().unwrap()).swap_bytes();
+        assert_eq!(word, round_tripped_on_be);
+    }
+}
+
+/// Brute-force reference for what a single stream's merged value should be, derived
+/// independently from `h3b`'s ladder offsets `[0, 4, 8, 16, 32, 64, 128]` rather than by
+/// calling the sketch's own (possibly buggy) merge code: a stored `value` only tells us
+/// the *worst-case* (minimum) rank consistent with it, `OFFSETS[value - 1]` above the `t`
+/// it was recorded against, so downgrading by `t_offset` asks how many tiers that
+/// worst-case rank still clears once the effective `t` rises by `t_offset`.
+#[cfg(test)]
+#[allow(clippy::cast_possible_truncation)] // `idx` only ever ranges over `OFFSETS`, i.e. 0..7
+fn expected_merged_value(self_value: u8, other_value: u8, t_offset: u32) -> u8 {
+    const OFFSETS: [u32; 7] = [0, 4, 8, 16, 32, 64, 128];
+
+    // Mirrors `HyperThreeBits::merge`'s own discard threshold: once the sketches are
+    // more than two rescale steps apart, the smaller-`t` one is dropped entirely.
+    if t_offset > 8 {
+        return self_value;
+    }
+    if t_offset == 0 {
+        // Equal `t`: plane-wise OR, equivalent to a bitwise OR of the packed values
+        // since the planes encode `value`'s bits directly (see `M64::val`/`set`).
+        return self_value | other_value;
+    }
+
+    let downgraded = if other_value == 0 {
+        0
+    } else {
+        let min_rank = OFFSETS[usize::from(other_value) - 1];
+        let mut tier = 0u8;
+        for (idx, &offset) in OFFSETS.iter().enumerate() {
+            if min_rank >= t_offset + offset {
+                tier = (idx + 1) as u8;
+            } else {
+                break;
+            }
+        }
+        tier
+    };
+    self_value.max(downgraded)
+}
+
+#[test]
+fn test_merge_matches_brute_force_reference_across_t_offsets() {
+    // Sweeps every `t` offset a merge can meaningfully see: equal (0), one rescale step
+    // ahead (4), the furthest a downgrade still bridges (8, per `downgrade`'s own
+    // `debug_assert!(steps <= 2)`), and just past the discard cutoff (12). Exhaustively
+    // pairs every value 0..=7 for a single stream against every other value, which
+    // covers the `same`, `merge_high_into_lo`, and discard branches of
+    // `HyperThreeBits::merge` in full.
+    for t_offset in [0u32, 4, 8, 12] {
+        for self_value in 0u8..=7 {
+            for other_value in 0u8..=7 {
+                let mut a: HyperThreeBits<M64> = HyperThreeBits::new();
+                a.t = 1 + t_offset;
+                a.sketch.set(0, self_value);
+                a.count = a.sketch.count();
+
+                let mut b: HyperThreeBits<M64> = HyperThreeBits::new();
+                b.t = 1;
+                b.sketch.set(0, other_value);
+                b.count = b.sketch.count();
+
+                a.merge(b);
+
+                let expected = expected_merged_value(self_value, other_value, t_offset);
+                assert_eq!(
+                    a.sketch.val(0),
+                    expected,
+                    "t_offset={t_offset}, self_value={self_value}, other_value={other_value}"
+                );
+            }
+        }
+    }
+}
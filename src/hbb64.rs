@@ -47,7 +47,14 @@ impl<HASH: Hasher + Default> HyperBitBit64<HASH> {
     pub fn insert<V: std::hash::Hash>(&mut self, v: V) {
         let mut x = HASH::default();
         v.hash(&mut x);
-        let x = x.finish();
+        self.insert_hash(x.finish());
+    }
+
+    #[inline]
+    /// Inserts an already-computed hash into the counter, bypassing `HyperBitBit64`'s own
+    /// hasher. Useful for feeding pre-hashed keys, or for sharing a single hash computation
+    /// across multiple counters fed the same values.
+    pub fn insert_hash(&mut self, x: u64) {
         let k: u32 = (x >> 58) as u32 % 64;
         let x: u64 = x & 0x03FF_FFFF_FFFF_FFFF;
 
@@ -86,4 +93,64 @@ impl<HASH: Hasher + Default> HyperBitBit64<HASH> {
         let bias: f64 = 1.1 * (1.0 / beta).ln();
         ((self.u as f64) * (Self::M as f64) * bias) as u64
     }
+
+    #[must_use]
+    /// Packs the counter's entire state (`sketch1`, `sketch2`, `count1`, `count2`, `u`,
+    /// in that order) into a fixed-size `[u64; 5]`, for callers that want to persist a
+    /// `HyperBitBit64` without pulling in serde for something this small. Round-trip
+    /// with [`Self::from_raw_parts`].
+    pub fn to_raw_parts(&self) -> [u64; 5] {
+        [self.sketch1, self.sketch2, self.count1, self.count2, self.u]
+    }
+
+    #[must_use]
+    /// Rebuilds a counter from the `[u64; 5]` produced by [`Self::to_raw_parts`].
+    /// Returns `None` if `count1` exceeds [`Self::M`], since that can't have come from
+    /// a valid counter and would make [`Self::count`] misbehave.
+    pub fn from_raw_parts(raw: [u64; 5]) -> Option<Self> {
+        let [sketch1, sketch2, count1, count2, u] = raw;
+        if count1 > Self::M {
+            return None;
+        }
+        Some(Self {
+            _hash: std::marker::PhantomData,
+            sketch1,
+            sketch2,
+            count1,
+            count2,
+            u,
+        })
+    }
+}
+
+// `HyperBitBit64` has no interior mutability, so it's `Send`/`Sync` for any
+// `Send + Sync` `HASH`; safe to share across threads or hold in async tasks.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HyperBitBit64>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::HyperBitBit64;
+
+    #[test]
+    fn test_to_from_raw_parts_roundtrip() {
+        let mut hbb: HyperBitBit64 = HyperBitBit64::new();
+        for i in 0..10_000u64 {
+            hbb.insert(i);
+        }
+
+        let raw = hbb.to_raw_parts();
+        let rebuilt: HyperBitBit64 = HyperBitBit64::from_raw_parts(raw).unwrap();
+
+        assert_eq!(raw, rebuilt.to_raw_parts());
+        assert_eq!(hbb.count(), rebuilt.count());
+    }
+
+    #[test]
+    fn test_from_raw_parts_rejects_count1_above_m() {
+        let raw = [0, 0, HyperBitBit64::<ahash::AHasher>::M + 1, 0, 1];
+        assert!(HyperBitBit64::<ahash::AHasher>::from_raw_parts(raw).is_none());
+    }
 }
@@ -0,0 +1,78 @@
+use std::hash::Hash;
+
+use crate::h2b::{HyperTwoBits, Sketch, M256};
+
+/// Adapter that lets [`HyperTwoBits`] stand in for the [`hyperloglog`] crate's
+/// `HyperLogLog` wherever calling code only uses `insert`/`len`. `hyperloglog` exposes
+/// those as inherent methods rather than a trait, so there's nothing to `impl` against;
+/// this type just matches the same two-method shape so a `s/HyperLogLog/HllCompat/`
+/// swap keeps compiling.
+///
+/// Semantic differences from `hyperloglog::HyperLogLog`:
+/// - `new` takes no error-rate parameter; precision is fixed by the `SKETCH` type
+///   parameter instead (default [`M256`]), matching every other `HyperTwoBits`
+///   constructor in this crate.
+/// - [`Self::len`] returns [`HyperTwoBits::count`]'s estimate as an `f64`, not a
+///   separately-computed estimator; `HyperTwoBits`'s own bias correction applies
+///   underneath it.
+#[derive(Debug, Clone)]
+pub struct HllCompat<SKETCH: Sketch = M256> {
+    inner: HyperTwoBits<SKETCH>,
+}
+
+impl<SKETCH: Sketch> Default for HllCompat<SKETCH> {
+    fn default() -> Self {
+        Self {
+            inner: HyperTwoBits::default(),
+        }
+    }
+}
+
+impl<SKETCH: Sketch> HllCompat<SKETCH> {
+    #[must_use]
+    /// Creates a new, empty counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    /// Inserts a value, mirroring `hyperloglog::HyperLogLog::insert`'s signature.
+    pub fn insert<V: Hash>(&mut self, value: &V) {
+        self.inner.insert(value);
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    /// Returns the cardinality estimate as an `f64`, mirroring
+    /// `hyperloglog::HyperLogLog::len`'s signature.
+    pub fn len(&self) -> f64 {
+        self.inner.count() as f64
+    }
+
+    #[must_use]
+    /// Returns `true` if no elements have been inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.inner.count() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HllCompat;
+    use crate::h2b::M4096;
+
+    #[test]
+    fn test_insert_and_len_through_compat_interface() {
+        let mut hll: HllCompat<M4096> = HllCompat::new();
+        assert!(hll.is_empty());
+
+        for i in 0..20_000u64 {
+            hll.insert(&i);
+        }
+
+        assert!(!hll.is_empty());
+        let actual = 20_000.0;
+        let delta = (actual - hll.len()).abs() / actual;
+        assert!(delta < 0.15, "delta too high: {delta}, len: {}", hll.len());
+    }
+}
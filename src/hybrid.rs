@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+use crate::h2b::{HyperTwoBits, Sketch, M256};
+use crate::AHasherDefaultBuilder;
+
+/// Exact-until-large cardinality counter: keeps an exact `HashSet` of hashed values
+/// until it holds more than `N` elements, then drains into a [`HyperTwoBits`] and
+/// continues estimating from there. This gives exact counts for workloads that are
+/// usually tiny but occasionally huge, without paying the sketch's estimation error on
+/// the common small case.
+#[derive(Debug, Clone)]
+pub struct HybridCounter<
+    const N: usize,
+    SKETCH: Sketch = M256,
+    HASH: BuildHasher + Default = AHasherDefaultBuilder,
+> {
+    hash: HASH,
+    state: State<SKETCH>,
+}
+
+#[derive(Debug, Clone)]
+enum State<SKETCH: Sketch> {
+    Exact(HashSet<u64>),
+    // Boxed so `State` (and thus `HybridCounter`) doesn't balloon to `HyperTwoBits`'s
+    // full size for the common case that never leaves `Exact` -- most pronounced once
+    // the `minhash` feature adds its bottom-k sample to every sketch.
+    Sketch(Box<HyperTwoBits<SKETCH>>),
+}
+
+impl<const N: usize, SKETCH: Sketch, HASH: BuildHasher + Default> Default
+    for HybridCounter<N, SKETCH, HASH>
+{
+    fn default() -> Self {
+        Self {
+            hash: HASH::default(),
+            state: State::Exact(HashSet::new()),
+        }
+    }
+}
+
+impl<const N: usize, SKETCH: Sketch, HASH: BuildHasher + Default> HybridCounter<N, SKETCH, HASH> {
+    #[must_use]
+    /// Creates a new `HybridCounter`, exact until it holds more than `N` elements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    /// Inserts a value into the counter, transparently switching from exact to
+    /// approximate counting once more than `N` distinct elements have been seen.
+    pub fn insert<V: Hash + ?Sized>(&mut self, v: &V) {
+        let hash = self.hash.hash_one(v);
+        match &mut self.state {
+            State::Exact(seen) => {
+                seen.insert(hash);
+                if seen.len() > N {
+                    let mut sketch = HyperTwoBits::<SKETCH>::new();
+                    sketch.populate_from_hashes(seen.iter().copied());
+                    self.state = State::Sketch(Box::new(sketch));
+                }
+            }
+            State::Sketch(sketch) => sketch.insert_hash(hash),
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    /// Returns the exact count while under `N` elements, or the sketch's estimate once
+    /// past it, transparently.
+    pub fn count(&self) -> u64 {
+        match &self.state {
+            State::Exact(seen) => seen.len() as u64,
+            State::Sketch(sketch) => sketch.count(),
+        }
+    }
+
+    #[must_use]
+    /// Returns `true` if the counter is still counting exactly, i.e. has not yet
+    /// exceeded `N` distinct elements.
+    pub fn is_exact(&self) -> bool {
+        matches!(self.state, State::Exact(_))
+    }
+}
+
+// `HybridCounter` has no interior mutability (its `Exact`/`Sketch` state lives behind
+// a plain `enum`, not a `Cell`), so it's `Send`/`Sync` for any `Send + Sync`
+// `SKETCH`/`HASH`. If a future `count()` caching change introduces a `Cell` here,
+// switch to an atomic or update this assertion to document the lost `Sync`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HybridCounter<1_000>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::HybridCounter;
+
+    #[test]
+    fn test_exact_below_threshold() {
+        let mut counter: HybridCounter<1_000> = HybridCounter::new();
+        for i in 0..999u64 {
+            counter.insert(&i);
+        }
+        assert!(counter.is_exact());
+        assert_eq!(counter.count(), 999);
+    }
+
+    #[test]
+    fn test_approximate_above_threshold() {
+        let mut counter: HybridCounter<1_000> = HybridCounter::new();
+        for i in 0..2_000u64 {
+            counter.insert(&i);
+        }
+        assert!(!counter.is_exact());
+        #[allow(clippy::cast_precision_loss)]
+        let delta = (2_000.0 - counter.count() as f64).abs() / 2_000.0;
+        assert!(
+            delta < 0.15,
+            "delta too high: {delta}, count: {}",
+            counter.count()
+        );
+    }
+}
@@ -13,22 +13,50 @@
 //! htb.insert(&"bar");
 //! htb.count();
 //! ```
+//!
+//! All sketch types in this crate (`HyperTwoBits`, `HyperThreeBits`, `HyperBitBit64`,
+//! `HybridCounter`, `CombinedEstimator`, `SketchReducer`) have no interior mutability, so
+//! they are `Send`/`Sync` whenever their `SKETCH`/`HASH` type parameters are. Each module
+//! has a compile-time assertion checking this for its default instantiation.
 
 #![deny(clippy::pedantic, missing_docs)]
 /// `HyperTwoBits` implementation
 pub mod h2b;
 /// `HyperBitBit64` implementation
 pub mod hbb64;
+/// Adapter matching the `hyperloglog` crate's `insert`/`len` interface (`hll-compat` feature)
+#[cfg(feature = "hll-compat")]
+pub mod hll_compat;
 
+/// Combines `HyperBitBit64` and `HyperTwoBits` for small-range accuracy
+pub mod combined;
 /// `HyperThreeBits` implementation
 pub mod h3b;
+/// Exact-until-large hybrid counter built on `HyperTwoBits`
+pub mod hybrid;
 /// Prelude for easy importing
 pub mod prelude;
+/// Streaming merge-reducer for combining `HyperTwoBits` shards incrementally
+pub mod reducer;
+/// Approximate sliding-window distinct counter built on `HyperTwoBits`
+pub mod window;
+
+mod register;
 
 use std::hash::{BuildHasher, BuildHasherDefault, Hasher as _};
 
 pub use prelude::*;
 
+/// Hasher builders whose seed can be replaced after construction, for
+/// `HyperTwoBits::rotate_seed`'s periodic reseeding scheme. Only the explicitly seeded
+/// builders ([`AHasherBuilder`], [`SipHasher13Builder`]) implement this -- the
+/// `*DefaultBuilder` type aliases wrap `BuildHasherDefault`, which carries no seed
+/// state to rotate.
+pub trait Seedable: BuildHasher {
+    /// Replaces this builder's seed, discarding the old one.
+    fn reseed(&mut self, seed: u64);
+}
+
 /// Random Seeded `AHasher` Builder that allows for seeded hashing per `HyperTwoBit` isnstance
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 #[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
@@ -54,6 +82,12 @@ impl BuildHasher for AHasherBuilder {
     }
 }
 
+impl Seedable for AHasherBuilder {
+    fn reseed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+}
+
 /// Non seeded `AHasher` Builder that is fater but will create completely predictable results
 pub type AHasherDefaultBuilder = BuildHasherDefault<ahash::AHasher>;
 
@@ -85,3 +119,10 @@ impl BuildHasher for SipHasher13Builder {
 #[cfg(feature = "siphash")]
 /// Non seeded `SipHasher13` Builder that is fater but will create completely predictable results
 pub type SipHasher13DefaultBuilder = BuildHasherDefault<siphasher::sip::SipHasher13>;
+
+#[cfg(feature = "siphash")]
+impl Seedable for SipHasher13Builder {
+    fn reseed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+}
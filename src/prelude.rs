@@ -1,4 +1,10 @@
+pub use crate::combined::CombinedEstimator;
 pub use crate::h2b;
 pub use crate::h3b;
 pub use crate::hbb64::HyperBitBit64;
+#[cfg(feature = "hll-compat")]
+pub use crate::hll_compat::HllCompat;
+pub use crate::hybrid::HybridCounter;
+pub use crate::reducer::SketchReducer;
+pub use crate::window::WindowedCounter;
 pub use crate::AHasherBuilder;
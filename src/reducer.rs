@@ -0,0 +1,144 @@
+use std::hash::BuildHasher;
+
+use crate::h2b::{HyperTwoBits, Sketch, M256};
+use crate::AHasherDefaultBuilder;
+
+/// Accumulates [`HyperTwoBits`] shards arriving one at a time (e.g. deserialized off a
+/// channel) into a running cardinality estimate, without holding on to every shard.
+///
+/// There's no seed value to construct a counter from up front — a fresh `HyperTwoBits`
+/// isn't a valid merge target until it shares a hasher with the shards being merged in
+/// (see [`HyperTwoBits::merge`]'s hasher-equality assertion) — so the reducer instead
+/// takes its first pushed sketch as the seed and merges every subsequent one into it.
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[derive(Debug, Clone)]
+pub struct SketchReducer<
+    SKETCH: Sketch = M256,
+    HASH: BuildHasher + Default = AHasherDefaultBuilder,
+    const RESCALE_STEP: u32 = 4,
+> {
+    seed: Option<HyperTwoBits<SKETCH, HASH, RESCALE_STEP>>,
+}
+
+impl<SKETCH: Sketch, HASH: BuildHasher + Default, const RESCALE_STEP: u32> Default
+    for SketchReducer<SKETCH, HASH, RESCALE_STEP>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SKETCH: Sketch, HASH: BuildHasher + Default, const RESCALE_STEP: u32>
+    SketchReducer<SKETCH, HASH, RESCALE_STEP>
+{
+    #[must_use]
+    /// Creates an empty reducer with no shards pushed yet.
+    pub fn new() -> Self {
+        Self { seed: None }
+    }
+
+    /// Merges `s` into the running estimate. The first call just takes `s` as the seed;
+    /// every subsequent call merges `s` into that seed via [`HyperTwoBits::merge`].
+    /// # Panics
+    /// If a pushed sketch's hasher doesn't match the seed's, per [`HyperTwoBits::merge`].
+    pub fn push(&mut self, s: HyperTwoBits<SKETCH, HASH, RESCALE_STEP>) {
+        match &mut self.seed {
+            Some(seed) => seed.merge(s),
+            None => self.seed = Some(s),
+        }
+    }
+
+    #[must_use]
+    /// Returns the running cardinality estimate, `0` if nothing has been pushed yet.
+    pub fn estimate(&self) -> u64 {
+        self.seed.as_ref().map_or(0, HyperTwoBits::count)
+    }
+}
+
+/// Merges `shards` one at a time through a fresh [`SketchReducer`], yielding the
+/// running cardinality estimate right after each merge instead of only exposing the
+/// final one.
+///
+/// For a map-reduce whose reducer streams shards in as they arrive (off a channel, or
+/// while paging through storage), this lets a caller show progress -- e.g. driving a
+/// UI counter up -- without holding on to every shard or re-deriving the estimate
+/// itself. The iterator is lazy: nothing is merged until it's polled.
+pub fn fold_estimates<SKETCH, HASH, const RESCALE_STEP: u32, I>(
+    shards: I,
+) -> impl Iterator<Item = u64>
+where
+    SKETCH: Sketch,
+    HASH: BuildHasher + Default,
+    I: IntoIterator<Item = HyperTwoBits<SKETCH, HASH, RESCALE_STEP>>,
+{
+    let mut reducer: SketchReducer<SKETCH, HASH, RESCALE_STEP> = SketchReducer::new();
+    shards.into_iter().map(move |shard| {
+        reducer.push(shard);
+        reducer.estimate()
+    })
+}
+
+// `SketchReducer` has no interior mutability, so it's `Send`/`Sync` for any
+// `Send + Sync` `SKETCH`/`HASH`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SketchReducer<M256>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::{fold_estimates, SketchReducer};
+    use crate::h2b::{HyperTwoBits, M256};
+
+    #[test]
+    fn test_reducer_estimate_matches_direct_merge() {
+        let shard_count = 8u64;
+        let per_shard = 5_000u64;
+
+        let mut reducer: SketchReducer<M256> = SketchReducer::new();
+        let mut direct: Option<HyperTwoBits<M256>> = None;
+        for shard in 0..shard_count {
+            let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+            for i in 0..per_shard {
+                htb.insert(&(shard * per_shard + i));
+            }
+            reducer.push(htb.clone());
+            match &mut direct {
+                Some(d) => d.merge(htb),
+                None => direct = Some(htb),
+            }
+        }
+
+        assert_eq!(reducer.estimate(), direct.unwrap().count());
+    }
+
+    #[test]
+    fn test_reducer_empty_estimate_is_zero() {
+        let reducer: SketchReducer<M256> = SketchReducer::new();
+        assert_eq!(reducer.estimate(), 0);
+    }
+
+    #[test]
+    fn test_fold_estimates_final_value_matches_merge_all() {
+        let shard_count = 8u64;
+        let per_shard = 5_000u64;
+
+        let mut shards = Vec::new();
+        let mut merge_all: Option<HyperTwoBits<M256>> = None;
+        for shard in 0..shard_count {
+            let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+            for i in 0..per_shard {
+                htb.insert(&(shard * per_shard + i));
+            }
+            shards.push(htb.clone());
+            match &mut merge_all {
+                Some(m) => m.merge(htb),
+                None => merge_all = Some(htb),
+            }
+        }
+
+        let estimates: Vec<u64> = fold_estimates(shards).collect();
+        assert_eq!(estimates.len(), usize::try_from(shard_count).unwrap());
+        assert_eq!(*estimates.last().unwrap(), merge_all.unwrap().count());
+    }
+}
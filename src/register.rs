@@ -0,0 +1,627 @@
+//! Shared bit-plane register storage used by the `h2b` and `h3b` sketch modules.
+//!
+//! Both modules store one value per substream by splitting it across `PLANES` bit
+//! planes (2 for `h2b`, 3 for `h3b`) and packing one bit of the value per plane into a
+//! `u128`. Parameterizing over `PLANES` keeps this storage logic in one place instead
+//! of two near-identical copies.
+
+/// Counts the set bits in `bits`, using the hardware `popcnt` instruction when the
+/// `popcnt` target feature is enabled and a branchless SWAR (SIMD-within-a-register)
+/// fallback otherwise. That target feature isn't on by default for a stock
+/// `cargo build`/`test`/`bench` on `x86_64` or `aarch64` -- it needs an explicit
+/// `-C target-feature=+popcnt` or a baseline like `target-cpu=native`/`x86-64-v2` --
+/// so `swar_popcount128` is the path most builds of this crate actually take, not a
+/// rare cross-compile fallback. It matters here either way since this is on the hot
+/// path for every [`BitRegister::count`] call.
+#[inline]
+pub(crate) fn popcount128(bits: u128) -> u32 {
+    #[cfg(target_feature = "popcnt")]
+    {
+        bits.count_ones()
+    }
+    #[cfg(not(target_feature = "popcnt"))]
+    {
+        swar_popcount128(bits)
+    }
+}
+
+/// SWAR popcount, generalizing the classic 64 bit bit-twiddling algorithm to 128 bits.
+/// Kept unconditionally compiled (not just under `not(target_feature = "popcnt")`) so
+/// it can be tested against `u128::count_ones` directly regardless of which path
+/// [`popcount128`] actually takes on the host running the tests.
+#[inline]
+pub(crate) fn swar_popcount128(bits: u128) -> u32 {
+    const M1: u128 = 0x5555_5555_5555_5555_5555_5555_5555_5555;
+    const M2: u128 = 0x3333_3333_3333_3333_3333_3333_3333_3333;
+    const M4: u128 = 0x0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f;
+    const H01: u128 = 0x0101_0101_0101_0101_0101_0101_0101_0101;
+
+    let x = bits - ((bits >> 1) & M1);
+    let x = (x & M2) + ((x >> 2) & M2);
+    let x = (x + (x >> 4)) & M4;
+    #[allow(clippy::cast_possible_truncation)]
+    let count = (x.wrapping_mul(H01) >> 120) as u32;
+    count
+}
+
+/// A single 128 bit wide register holding `PLANES` bit-planes, one bit of a
+/// substream's value per plane. Plane 0 is the low bit, plane `PLANES - 1` the high
+/// bit.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+pub(crate) struct BitRegister<const PLANES: usize> {
+    planes: [u128; PLANES],
+}
+
+impl<const PLANES: usize> Default for BitRegister<PLANES> {
+    fn default() -> Self {
+        Self {
+            planes: [0; PLANES],
+        }
+    }
+}
+
+impl<const PLANES: usize> BitRegister<PLANES> {
+    #[inline]
+    pub(crate) fn val(&self, bit_index: usize) -> u8 {
+        let mut value = 0;
+        for (plane, bits) in self.planes.iter().enumerate() {
+            value |= (((bits >> bit_index) & 1) as u8) << plane;
+        }
+        value
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, bit_index: usize, value: u8) {
+        for (plane, bits) in self.planes.iter_mut().enumerate() {
+            *bits &= !(1 << bit_index);
+            *bits |= u128::from((value >> plane) & 1) << bit_index;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn count(&self) -> u32 {
+        popcount128(self.planes.iter().fold(0, |used, plane| used | plane))
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "simd", allow(dead_code))]
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (plane, other_plane) in self.planes.iter_mut().zip(other.planes.iter()) {
+            *plane |= other_plane;
+        }
+    }
+
+    /// Merges `other`'s planes one step down: plane `i` receives plane `i + 1` of
+    /// `other`, leaving the top plane untouched. This mirrors merging a sketch whose
+    /// `t` is one rescale step behind into this one.
+    #[inline]
+    pub(crate) fn merge_high_into_lo(&mut self, other: &Self) {
+        for plane in 0..PLANES - 1 {
+            self.planes[plane] |= other.planes[plane + 1];
+        }
+    }
+
+    #[inline]
+    /// Yields this register's plane words high-plane-first, i.e. reversed from how
+    /// they're stored (`planes[0]` is the low plane, see the struct docs above).
+    pub(crate) fn plane_words_high_to_low(&self) -> impl Iterator<Item = u128> + '_ {
+        self.planes.iter().rev().copied()
+    }
+
+    #[inline]
+    /// Rebuilds a register from exactly `PLANES` words in the same high-to-low order
+    /// [`Self::plane_words_high_to_low`] yields.
+    /// # Panics
+    /// Panics if `words` yields fewer than `PLANES` items.
+    pub(crate) fn from_planes_high_to_low(mut words: impl Iterator<Item = u128>) -> Self {
+        let mut planes = [0u128; PLANES];
+        for plane in planes.iter_mut().rev() {
+            *plane = words.next().expect("not enough plane words");
+        }
+        Self { planes }
+    }
+
+    #[cfg(feature = "simd")]
+    /// SIMD-accelerated equivalent of [`Self::merge`]: splits each `u128` plane into
+    /// two 64-bit halves and ORs them four lanes at a time via `wide::u64x4`, instead
+    /// of leaving the `u128` OR loop to the compiler's own auto-vectorization. Only
+    /// valid for a same-`t` merge, same as [`Self::merge`]; a differing-`t` merge still
+    /// needs [`Self::merge_high_into_lo`]'s scalar shuffle.
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn merge_simd(&mut self, other: &Self) {
+        let mut plane = 0;
+        while plane + 2 <= PLANES {
+            let a = wide::u64x4::new([
+                self.planes[plane] as u64,
+                (self.planes[plane] >> 64) as u64,
+                self.planes[plane + 1] as u64,
+                (self.planes[plane + 1] >> 64) as u64,
+            ]);
+            let b = wide::u64x4::new([
+                other.planes[plane] as u64,
+                (other.planes[plane] >> 64) as u64,
+                other.planes[plane + 1] as u64,
+                (other.planes[plane + 1] >> 64) as u64,
+            ]);
+            let merged = (a | b).to_array();
+            self.planes[plane] = u128::from(merged[0]) | (u128::from(merged[1]) << 64);
+            self.planes[plane + 1] = u128::from(merged[2]) | (u128::from(merged[3]) << 64);
+            plane += 2;
+        }
+        for remaining in self.planes[plane..].iter_mut().zip(&other.planes[plane..]) {
+            *remaining.0 |= remaining.1;
+        }
+    }
+}
+
+impl BitRegister<2> {
+    /// Fast decrement for the two-plane (high/low) layout used by `h2b`: since we
+    /// decrement, the new count equals the number of substreams that were set to 2 or
+    /// 3, so we can compute it cheaply from the high plane alone before shifting it
+    /// down into the low plane.
+    #[inline]
+    pub(crate) fn decrement(&mut self) -> u32 {
+        let count = popcount128(self.planes[1]);
+        self.planes[0] = self.planes[1] & !self.planes[0];
+        self.planes[1] &= !self.planes[0];
+        count
+    }
+}
+
+/// Generic sketch using `REGISTERS` 128 bit [`BitRegister`]s, so the total M for the
+/// sketch is `REGISTERS * 128`, each storing `PLANES` bit-planes per substream.
+///
+/// This is not meant to be used directly, instead it serves as the storage backing
+/// for the vectored `M256`..`M8192` sketches in both `h2b` and `h3b`.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+pub(crate) struct M128Reg<const REGISTERS: usize, const PLANES: usize> {
+    registers: [BitRegister<PLANES>; REGISTERS],
+}
+
+impl<const REGISTERS: usize, const PLANES: usize> Default for M128Reg<REGISTERS, PLANES> {
+    fn default() -> Self {
+        Self {
+            registers: [BitRegister::default(); REGISTERS],
+        }
+    }
+}
+
+impl<const REGISTERS: usize> M128Reg<REGISTERS, 2> {
+    /// Fast decrement built on [`BitRegister::<2>::decrement`], see there for details.
+    #[inline]
+    pub(crate) fn decrement(&mut self) -> u32 {
+        self.registers.iter_mut().map(BitRegister::decrement).sum()
+    }
+}
+
+impl<const REGISTERS: usize, const PLANES: usize> M128Reg<REGISTERS, PLANES> {
+    const REG_SIZE: usize = 128;
+
+    #[inline]
+    pub(crate) fn val(&self, stream: u32) -> u8 {
+        let register_index = stream as usize / Self::REG_SIZE;
+        let bit_index = stream as usize % Self::REG_SIZE;
+        self.registers[register_index].val(bit_index)
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, stream: u32, value: u8) {
+        let register_index = stream as usize / Self::REG_SIZE;
+        let bit_index = stream as usize % Self::REG_SIZE;
+        self.registers[register_index].set(bit_index, value);
+    }
+
+    #[inline]
+    pub(crate) fn count(&self) -> u32 {
+        self.registers.iter().map(BitRegister::count).sum()
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "simd", allow(dead_code))]
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            register.merge(other_register);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn merge_high_into_lo(&mut self, other: &Self) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            register.merge_high_into_lo(other_register);
+        }
+    }
+
+    #[inline]
+    /// Yields all registers' plane words in order, each register contributing its
+    /// planes high-to-low, see [`BitRegister::plane_words_high_to_low`].
+    pub(crate) fn plane_words(&self) -> impl Iterator<Item = u128> + '_ {
+        self.registers
+            .iter()
+            .flat_map(BitRegister::plane_words_high_to_low)
+    }
+
+    #[inline]
+    /// Rebuilds all registers from `REGISTERS * PLANES` words in the same order
+    /// [`Self::plane_words`] yields.
+    /// # Panics
+    /// Panics if `words` yields fewer than `REGISTERS * PLANES` items.
+    pub(crate) fn from_raw_planes(mut words: impl Iterator<Item = u128>) -> Self {
+        let mut registers = [BitRegister::default(); REGISTERS];
+        for register in &mut registers {
+            *register = BitRegister::from_planes_high_to_low((&mut words).take(PLANES));
+        }
+        Self { registers }
+    }
+
+    #[cfg(feature = "simd")]
+    /// SIMD-accelerated equivalent of [`Self::merge`], see [`BitRegister::merge_simd`].
+    #[inline]
+    pub(crate) fn merge_simd(&mut self, other: &Self) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            register.merge_simd(other_register);
+        }
+    }
+}
+
+/// Counts the set bits in `bits`, the 64 bit counterpart to [`popcount128`] used by
+/// [`Register64`]. `u128` arithmetic (including `count_ones`) is emulated in software
+/// on 32-bit targets, so the `Register64`-backed sketch variant stays on native-width
+/// `u64` ops end to end instead of paying that emulation cost per insert.
+#[inline]
+#[cfg(any(target_pointer_width = "32", test))]
+pub(crate) fn popcount64(bits: u64) -> u32 {
+    #[cfg(target_feature = "popcnt")]
+    {
+        bits.count_ones()
+    }
+    #[cfg(not(target_feature = "popcnt"))]
+    {
+        swar_popcount64(bits)
+    }
+}
+
+/// SWAR popcount, the same bit-twiddling algorithm [`swar_popcount128`] generalizes to
+/// 128 bits, kept unconditionally compiled for the same reason: so it can be tested
+/// against `u64::count_ones` directly regardless of which path [`popcount64`] takes on
+/// the host running the tests.
+#[inline]
+#[cfg(any(target_pointer_width = "32", test))]
+pub(crate) fn swar_popcount64(bits: u64) -> u32 {
+    const M1: u64 = 0x5555_5555_5555_5555;
+    const M2: u64 = 0x3333_3333_3333_3333;
+    const M4: u64 = 0x0f0f_0f0f_0f0f_0f0f;
+    const H01: u64 = 0x0101_0101_0101_0101;
+
+    let x = bits - ((bits >> 1) & M1);
+    let x = (x & M2) + ((x >> 2) & M2);
+    let x = (x + (x >> 4)) & M4;
+    #[allow(clippy::cast_possible_truncation)]
+    let count = (x.wrapping_mul(H01) >> 56) as u32;
+    count
+}
+
+/// A single 64 bit wide register holding `PLANES` bit-planes, the `u64` counterpart to
+/// [`BitRegister`] for the `Register64`-backed sketch variant (see
+/// [`crate::h2b::sketch::M64Reg`]) used on 32-bit targets, where `u128` ops are
+/// emulated rather than native.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[cfg(any(target_pointer_width = "32", test))]
+pub(crate) struct Register64<const PLANES: usize> {
+    planes: [u64; PLANES],
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const PLANES: usize> Default for Register64<PLANES> {
+    fn default() -> Self {
+        Self {
+            planes: [0; PLANES],
+        }
+    }
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const PLANES: usize> Register64<PLANES> {
+    #[inline]
+    pub(crate) fn val(&self, bit_index: usize) -> u8 {
+        let mut value = 0;
+        for (plane, bits) in self.planes.iter().enumerate() {
+            value |= (((bits >> bit_index) & 1) as u8) << plane;
+        }
+        value
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, bit_index: usize, value: u8) {
+        for (plane, bits) in self.planes.iter_mut().enumerate() {
+            *bits &= !(1 << bit_index);
+            *bits |= u64::from((value >> plane) & 1) << bit_index;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn count(&self) -> u32 {
+        popcount64(self.planes.iter().fold(0, |used, plane| used | plane))
+    }
+
+    #[inline]
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (plane, other_plane) in self.planes.iter_mut().zip(other.planes.iter()) {
+            *plane |= other_plane;
+        }
+    }
+
+    /// Merges `other`'s planes one step down, see [`BitRegister::merge_high_into_lo`].
+    #[inline]
+    pub(crate) fn merge_high_into_lo(&mut self, other: &Self) {
+        for plane in 0..PLANES - 1 {
+            self.planes[plane] |= other.planes[plane + 1];
+        }
+    }
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl Register64<2> {
+    /// Fast decrement for the two-plane (high/low) layout, see [`BitRegister::<2>::decrement`].
+    #[inline]
+    pub(crate) fn decrement(&mut self) -> u32 {
+        let count = popcount64(self.planes[1]);
+        self.planes[0] = self.planes[1] & !self.planes[0];
+        self.planes[1] &= !self.planes[0];
+        count
+    }
+}
+
+/// Generic sketch using `REGISTERS` 64 bit [`Register64`]s, so the total M for the
+/// sketch is `REGISTERS * 64`, each storing `PLANES` bit-planes per substream. The
+/// `u64` counterpart to [`M128Reg`], backing [`crate::h2b::sketch::M64Reg`] on 32-bit
+/// targets where `u128` ops are emulated rather than native.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[cfg(any(target_pointer_width = "32", test))]
+pub(crate) struct M64Reg<const REGISTERS: usize, const PLANES: usize> {
+    registers: [Register64<PLANES>; REGISTERS],
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const REGISTERS: usize, const PLANES: usize> Default for M64Reg<REGISTERS, PLANES> {
+    fn default() -> Self {
+        Self {
+            registers: [Register64::default(); REGISTERS],
+        }
+    }
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const REGISTERS: usize> M64Reg<REGISTERS, 2> {
+    /// Fast decrement built on [`Register64::<2>::decrement`], see there for details.
+    #[inline]
+    pub(crate) fn decrement(&mut self) -> u32 {
+        self.registers.iter_mut().map(Register64::decrement).sum()
+    }
+}
+
+#[cfg(any(target_pointer_width = "32", test))]
+impl<const REGISTERS: usize, const PLANES: usize> M64Reg<REGISTERS, PLANES> {
+    const REG_SIZE: usize = 64;
+
+    #[inline]
+    pub(crate) fn val(&self, stream: u32) -> u8 {
+        let register_index = stream as usize / Self::REG_SIZE;
+        let bit_index = stream as usize % Self::REG_SIZE;
+        self.registers[register_index].val(bit_index)
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, stream: u32, value: u8) {
+        let register_index = stream as usize / Self::REG_SIZE;
+        let bit_index = stream as usize % Self::REG_SIZE;
+        self.registers[register_index].set(bit_index, value);
+    }
+
+    #[inline]
+    pub(crate) fn count(&self) -> u32 {
+        self.registers.iter().map(Register64::count).sum()
+    }
+
+    #[inline]
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            register.merge(other_register);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn merge_high_into_lo(&mut self, other: &Self) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            register.merge_high_into_lo(other_register);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn test<const REGISTERS: usize, const PLANES: usize>(max_value: u8) {
+        let streams = (REGISTERS * 128) as u32;
+        let mut r = M128Reg::<REGISTERS, PLANES>::default();
+        for i in 0..streams {
+            assert_eq!(r.val(i), 0);
+            for v in 1..=max_value {
+                r.set(i, v);
+                assert_eq!(r.val(i), v);
+            }
+            r.set(i, 0);
+        }
+        for i in 0..streams {
+            r.set(i, max_value);
+        }
+        assert_eq!(r.count(), streams);
+    }
+
+    #[test]
+    fn test_two_planes() {
+        test::<2, 2>(3);
+    }
+
+    #[test]
+    fn test_three_planes() {
+        test::<2, 3>(7);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn test64<const REGISTERS: usize, const PLANES: usize>(max_value: u8) {
+        let streams = (REGISTERS * 64) as u32;
+        let mut r = M64Reg::<REGISTERS, PLANES>::default();
+        for i in 0..streams {
+            assert_eq!(r.val(i), 0);
+            for v in 1..=max_value {
+                r.set(i, v);
+                assert_eq!(r.val(i), v);
+            }
+            r.set(i, 0);
+        }
+        for i in 0..streams {
+            r.set(i, max_value);
+        }
+        assert_eq!(r.count(), streams);
+    }
+
+    #[test]
+    fn test_two_planes_64() {
+        test64::<2, 2>(3);
+    }
+
+    #[test]
+    fn test_three_planes_64() {
+        test64::<2, 3>(7);
+    }
+
+    // `M64Reg`/`Register64` are the `u64` counterpart to `M128Reg`/`BitRegister`, used
+    // on 32-bit targets where `u128` ops are emulated. Drive the same sequence of
+    // sets/decrements through both at an equal total `M` (`REGISTERS * 128 ==
+    // (REGISTERS * 2) * 64`) and check they agree bit-for-bit at every stream.
+    #[test]
+    fn m64reg_matches_m128reg_bit_for_bit() {
+        let mut wide = M128Reg::<2, 2>::default();
+        let mut narrow = M64Reg::<4, 2>::default();
+        let streams = 2 * 128;
+
+        for i in 0..streams {
+            let value = u8::try_from((i * 7 + 1) % 4).unwrap();
+            wide.set(i, value);
+            narrow.set(i, value);
+        }
+        for i in 0..streams {
+            assert_eq!(wide.val(i), narrow.val(i), "mismatch at stream {i}");
+        }
+        assert_eq!(wide.count(), narrow.count());
+
+        assert_eq!(wide.decrement(), narrow.decrement());
+        for i in 0..streams {
+            assert_eq!(
+                wide.val(i),
+                narrow.val(i),
+                "mismatch at stream {i} after decrement"
+            );
+        }
+    }
+
+    proptest::proptest! {
+        // The SWAR fallback must agree with the hardware intrinsic bit-for-bit on every
+        // input, since `popcount128` picks between them purely based on target
+        // features -- callers can't tell which one they got.
+        #[test]
+        fn swar_popcount_matches_count_ones(bits: u128) {
+            proptest::prop_assert_eq!(swar_popcount128(bits), bits.count_ones());
+        }
+
+        // Same guarantee as `swar_popcount_matches_count_ones`, for `Register64`'s
+        // 64-bit popcount.
+        #[test]
+        fn swar_popcount64_matches_count_ones(bits: u64) {
+            proptest::prop_assert_eq!(swar_popcount64(bits), bits.count_ones());
+        }
+
+        // `M64Reg::count` for a naive per-stream reference, the same check
+        // `count_matches_naive_two_planes` makes for `M128Reg`.
+        #[test]
+        fn count_matches_naive_two_planes_64(values in proptest::collection::vec(0u8..=3, 128)) {
+            let mut r = M64Reg::<2, 2>::default();
+            for (stream, &value) in values.iter().enumerate() {
+                r.set(u32::try_from(stream).unwrap(), value);
+            }
+            let naive = u32::try_from(values.iter().filter(|&&v| v > 0).count()).unwrap();
+            proptest::prop_assert_eq!(r.count(), naive);
+        }
+
+        // `count` ORs all planes into a single active-stream mask and pop-counts it in
+        // one pass rather than looping and branching per stream; verify that fast path
+        // agrees with a naive per-stream reference for both the two-plane (h2b) and
+        // three-plane (h3b) layouts.
+        #[test]
+        fn count_matches_naive_two_planes(values in proptest::collection::vec(0u8..=3, 256)) {
+            let mut r = M128Reg::<2, 2>::default();
+            for (stream, &value) in values.iter().enumerate() {
+                r.set(u32::try_from(stream).unwrap(), value);
+            }
+            let naive = u32::try_from(values.iter().filter(|&&v| v > 0).count()).unwrap();
+            proptest::prop_assert_eq!(r.count(), naive);
+        }
+
+        #[test]
+        fn count_matches_naive_three_planes(values in proptest::collection::vec(0u8..=7, 256)) {
+            let mut r = M128Reg::<2, 3>::default();
+            for (stream, &value) in values.iter().enumerate() {
+                r.set(u32::try_from(stream).unwrap(), value);
+            }
+            let naive = u32::try_from(values.iter().filter(|&&v| v > 0).count()).unwrap();
+            proptest::prop_assert_eq!(r.count(), naive);
+        }
+
+        #[cfg(feature = "simd")]
+        #[test]
+        fn merge_simd_matches_scalar_two_planes(
+            a_values in proptest::collection::vec(0u8..=3, 256),
+            b_values in proptest::collection::vec(0u8..=3, 256),
+        ) {
+            let mut scalar = M128Reg::<2, 2>::default();
+            let mut simd = M128Reg::<2, 2>::default();
+            let mut other = M128Reg::<2, 2>::default();
+            for (stream, (&a, &b)) in a_values.iter().zip(b_values.iter()).enumerate() {
+                let stream = u32::try_from(stream).unwrap();
+                scalar.set(stream, a);
+                simd.set(stream, a);
+                other.set(stream, b);
+            }
+            scalar.merge(&other);
+            simd.merge_simd(&other);
+            proptest::prop_assert_eq!(scalar, simd);
+        }
+
+        #[cfg(feature = "simd")]
+        #[test]
+        fn merge_simd_matches_scalar_three_planes(
+            a_values in proptest::collection::vec(0u8..=7, 256),
+            b_values in proptest::collection::vec(0u8..=7, 256),
+        ) {
+            let mut scalar = M128Reg::<2, 3>::default();
+            let mut simd = M128Reg::<2, 3>::default();
+            let mut other = M128Reg::<2, 3>::default();
+            for (stream, (&a, &b)) in a_values.iter().zip(b_values.iter()).enumerate() {
+                let stream = u32::try_from(stream).unwrap();
+                scalar.set(stream, a);
+                simd.set(stream, a);
+                other.set(stream, b);
+            }
+            scalar.merge(&other);
+            simd.merge_simd(&other);
+            proptest::prop_assert_eq!(scalar, simd);
+        }
+    }
+}
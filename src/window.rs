@@ -0,0 +1,138 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::h2b::{HyperTwoBits, Sketch, M256};
+use crate::AHasherDefaultBuilder;
+
+/// Approximate sliding-window distinct counter: keeps `W` per-slot [`HyperTwoBits`]
+/// sketches, one per time bucket, and reports the union of the slots currently in the
+/// window as the estimate. [`HyperTwoBits::merge`] only ever grows a sketch -- there's
+/// no way to subtract a slot's contribution back out once merged in -- so eviction
+/// here instead drops the oldest slot's sketch outright and starts it fresh, rather
+/// than merging into (and never being able to shrink) a single running estimate.
+///
+/// `HASH` must build identical hashers across separate `default()` calls, since
+/// [`Self::advance`] replaces an evicted slot with a fresh one that then needs to
+/// merge with the others in [`Self::count`] -- true of the stateless
+/// `*DefaultBuilder` hashers this crate defaults to, but not of the explicitly seeded
+/// [`crate::AHasherBuilder`]/[`crate::SipHasher13Builder`], which pick a new random
+/// seed on every `default()` call.
+#[derive(Debug, Clone)]
+pub struct WindowedCounter<
+    const W: usize,
+    SKETCH: Sketch + Clone = M256,
+    HASH: BuildHasher + Default + Clone = AHasherDefaultBuilder,
+> {
+    slots: [HyperTwoBits<SKETCH, HASH>; W],
+    current: usize,
+}
+
+impl<const W: usize, SKETCH: Sketch + Clone, HASH: BuildHasher + Default + Clone> Default
+    for WindowedCounter<W, SKETCH, HASH>
+{
+    fn default() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| HyperTwoBits::default()),
+            current: 0,
+        }
+    }
+}
+
+impl<const W: usize, SKETCH: Sketch + Clone, HASH: BuildHasher + Default + Clone>
+    WindowedCounter<W, SKETCH, HASH>
+{
+    #[must_use]
+    /// Creates an empty windowed counter with all `W` slots empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a value into the current (most recent) slot.
+    pub fn insert<V: Hash + ?Sized>(&mut self, v: &V) {
+        self.slots[self.current].insert(v);
+    }
+
+    /// Advances the window by one slot: the slot that's about to become current (the
+    /// oldest live slot, `W` advances ago) is dropped and replaced with an empty
+    /// sketch, and subsequent [`Self::insert`] calls go into it. Call this once per
+    /// time bucket (e.g. once a minute for a one-hour window split into 60 one-minute
+    /// slots).
+    /// # Panics
+    /// If `W` is `0`.
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % W;
+        self.slots[self.current] = HyperTwoBits::default();
+    }
+
+    #[must_use]
+    /// Returns the estimated distinct count across all `W` live slots, as their union.
+    /// Non-destructive: clones each slot's sketch into a scratch accumulator rather
+    /// than merging the live slots together.
+    /// # Panics
+    /// If `W` is `0`.
+    pub fn count(&self) -> u64 {
+        let mut union = self.slots[0].clone();
+        for slot in &self.slots[1..] {
+            union.merge(slot.clone());
+        }
+        union.count()
+    }
+}
+
+// `WindowedCounter` has no interior mutability, so it's `Send`/`Sync` for any
+// `Send + Sync` `SKETCH`/`HASH`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<WindowedCounter<4, M256>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::WindowedCounter;
+    use crate::h2b::M4096;
+
+    #[test]
+    fn test_advancing_through_slots_tracks_the_live_set() {
+        let mut windowed: WindowedCounter<3, M4096> = WindowedCounter::new();
+
+        for i in 0..1_000u64 {
+            windowed.insert(&i);
+        }
+        let first_window = windowed.count();
+        assert!(
+            (900..=1_100).contains(&first_window),
+            "expected roughly 1000, got {first_window}"
+        );
+
+        // Advance past every slot the first batch touched, evicting all of it.
+        for _ in 0..3 {
+            windowed.advance();
+        }
+        assert_eq!(
+            windowed.count(),
+            0,
+            "advancing W times should evict the whole first window"
+        );
+
+        for i in 1_000..1_500u64 {
+            windowed.insert(&i);
+        }
+        let second_window = windowed.count();
+        assert!(
+            (400..=600).contains(&second_window),
+            "expected roughly 500, got {second_window}"
+        );
+    }
+
+    #[test]
+    fn test_empty_windowed_counter_estimates_zero() {
+        let windowed: WindowedCounter<4> = WindowedCounter::new();
+        assert_eq!(windowed.count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to calculate the remainder with a divisor of zero")]
+    fn test_advance_panics_when_w_is_zero() {
+        let mut windowed: WindowedCounter<0, M4096> = WindowedCounter::new();
+        windowed.advance();
+    }
+}
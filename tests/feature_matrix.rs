@@ -0,0 +1,110 @@
+//! Smoke tests exercising pairs of independently-toggleable Cargo features together, so
+//! CI catches a feature-interaction that breaks compilation (e.g. `#[cfg]`-gated fields
+//! that only line up correctly when considered in isolation) before it reaches users
+//! combining features in ways no single feature's own tests would notice. Each test
+//! just constructs a sketch, inserts a handful of values, and counts under that
+//! combination -- exhaustive accuracy coverage for each feature lives in its own tests.
+
+// Depending on which feature combination is active, not every test in this file
+// compiles in, so these shared imports go unused in some combinations.
+#[cfg(all(feature = "raw", feature = "siphash"))]
+use hypertwobits::h2b::Sketch as _;
+#[allow(unused_imports)]
+use hypertwobits::h2b::{HyperTwoBits, M256};
+
+#[cfg(all(feature = "siphash", feature = "metrics"))]
+#[test]
+fn siphash_and_metrics_combine() {
+    use hypertwobits::SipHasher13DefaultBuilder;
+
+    let mut htb: HyperTwoBits<M256, SipHasher13DefaultBuilder> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    assert!(htb.count() > 0);
+    assert!(htb.metrics().estimate > 0);
+}
+
+#[cfg(all(feature = "simd", feature = "metrics"))]
+#[test]
+fn simd_and_metrics_combine() {
+    use hypertwobits::h2b::M4096;
+
+    let mut a: HyperTwoBits<M4096> = HyperTwoBits::new();
+    let mut b: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        a.insert(&i);
+    }
+    for i in 1_000..2_000u64 {
+        b.insert(&i);
+    }
+    a.merge(b);
+    assert!(a.count() > 0);
+    assert!(a.metrics().estimate > 0);
+}
+
+#[cfg(all(feature = "mem_dbg", feature = "siphash"))]
+#[test]
+fn mem_dbg_and_siphash_combine() {
+    let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    assert!(htb.count() > 0);
+}
+
+#[cfg(all(feature = "raw", feature = "siphash"))]
+#[test]
+fn raw_and_siphash_combine() {
+    let mut sketch = M256::default();
+    sketch.set(0, 1);
+    let htb: HyperTwoBits<M256> = HyperTwoBits::from_sketch(sketch, 1, 1);
+    assert_eq!(htb.count(), 1);
+
+    let mut plain: HyperTwoBits<M256> = HyperTwoBits::new();
+    plain.insert(&"x");
+    assert!(plain.count() > 0);
+}
+
+#[cfg(all(feature = "track-inserts", feature = "siphash"))]
+#[test]
+fn track_inserts_and_siphash_combine() {
+    let mut htb: HyperTwoBits<M256> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    assert_eq!(htb.total_inserts(), 1_000);
+    assert!(htb.count() > 0);
+}
+
+#[cfg(all(feature = "validation", feature = "siphash"))]
+#[test]
+fn validation_and_siphash_combine() {
+    let data: Vec<u32> = (0..1_000).collect();
+    let error = HyperTwoBits::<M256>::validate_accuracy(&data, 0.2).unwrap();
+    assert!(error <= 0.2);
+}
+
+#[cfg(all(feature = "fast-math", feature = "simd"))]
+#[test]
+fn fast_math_and_simd_combine() {
+    use hypertwobits::h2b::M4096;
+
+    let mut htb: HyperTwoBits<M4096> = HyperTwoBits::new();
+    for i in 0..1_000u64 {
+        htb.insert(&i);
+    }
+    assert!(htb.count() > 0);
+}
+
+#[cfg(all(feature = "hll-compat", feature = "siphash"))]
+#[test]
+fn hll_compat_and_siphash_combine() {
+    use hypertwobits::hll_compat::HllCompat;
+
+    let mut hll: HllCompat<M256> = HllCompat::default();
+    for i in 0..1_000u64 {
+        hll.insert(&i);
+    }
+    assert!(hll.len() > 0.0);
+}